@@ -1,98 +1,388 @@
 use eframe::egui;
-use crate::{theme::FadeTheme, search::AppSearcher, ui::LauncherUI};
-use std::time::{Duration, Instant};
+use crate::{app_info::AppInfo, config::{Config, ConfigProblem}, config_watcher::ConfigWatcher, ipc::{Command, IpcServer, Message}, rpc::{Rpc, RpcTarget}, theme::FadeTheme, search::{AppSearcher, IndexStats}, ui::LauncherUI, window};
+use serde_json::Value;
+use std::{
+    cell::RefCell,
+    path::Path,
+    rc::Rc,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
 
 pub struct FadeLauncher {
+    config: Config,
+    config_watcher: Option<ConfigWatcher>,
+    config_error: Option<String>, // Why the config file was last rejected
+    config_problems: Vec<ConfigProblem>, // Validation problems of `config`
+    ipc_server: Option<IpcServer>, // Commands from later invocations
+    daemon: bool, // Hide instead of exiting, until an explicit quit
+    hide_by_closing: bool, // The window can't be hidden, so a daemon closes it and waits without one
+    in_background: bool, // Closed to hide; see `run_in_background`
+    visible: bool,
+    was_focused: bool,
+    quitting: bool,
     search_query: String,
     search_results: Vec<AppInfo>,
     searcher: AppSearcher,
     ui: LauncherUI,
+    clear_shortcut: Option<egui::KeyboardShortcut>,
     last_search_time: Instant,
     animation_time: f32,
     show_settings: bool,
-}
-
-#[derive(Clone, Debug)]
-pub struct AppInfo {
-    pub name: String,
-    pub path: String,
-    pub icon_path: Option<String>,
-    pub description: Option<String>,
-    pub score: f32, // Relevance score for search results
+    was_indexing: bool,
+    filter_debug_path: String, // File checked against the filter rules in the settings window
+    setting_path: String, // Dotted path edited in the settings window
+    setting_value: String,
+    setting_status: Option<String>, // Outcome of the last change made in the settings window
+    unsaved_settings: Vec<String>, // Paths changed in the settings window since the last save
 }
 
 impl FadeLauncher {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        let mut searcher = AppSearcher::new();
+    pub fn new(config: Config, config_error: Option<String>, ipc_server: Option<IpcServer>, daemon: bool) -> Self {
+        let mut searcher = AppSearcher::from_config(&config.search);
         
         // Start initial app scanning in background
         searcher.start_initial_scan();
         
+        let ui = LauncherUI::new(&config.appearance, Self::shortcut(&config.hotkeys.launch_first));
+        let clear_shortcut = Self::shortcut(&config.hotkeys.clear_search);
+        
+        let config_problems = config.validate();
+        
+        // Without a socket nothing could bring a closed window back
+        let can_hide = window::can_hide_window();
+        let hide_by_closing = daemon && !can_hide && ipc_server.is_some();
+        if daemon && !can_hide && !hide_by_closing {
+            eprintln!("Windows can't be hidden on Wayland, so dismissing the launcher will quit it");
+        }
+        
         Self {
+            config,
+            config_watcher: ConfigWatcher::start(),
+            config_error,
+            config_problems,
+            ipc_server,
+            daemon,
+            hide_by_closing,
+            in_background: false,
+            visible: true,
+            was_focused: false,
+            quitting: false,
             search_query: String::new(),
             search_results: Vec::new(),
             searcher,
-            ui: LauncherUI::new(),
+            ui,
+            clear_shortcut,
             last_search_time: Instant::now(),
             animation_time: 0.0,
             show_settings: false,
+            was_indexing: false,
+            filter_debug_path: String::new(),
+            setting_path: String::new(),
+            setting_value: String::new(),
+            setting_status: None,
+            unsaved_settings: Vec::new(),
         }
     }
     
     fn update_search(&mut self) {
+        let limit = self.config.search.max_results;
         if self.search_query.is_empty() {
-            self.search_results = self.searcher.get_recent_apps(10);
+            self.search_results = self.searcher.get_recent_apps(limit);
         } else {
-            self.search_results = self.searcher.search(&self.search_query, 10);
+            self.search_results = self.searcher.search(&self.search_query, limit);
         }
         self.last_search_time = Instant::now();
     }
     
-    fn launch_app(&mut self, app: &AppInfo) {
+    fn launch_app(&mut self, ctx: &egui::Context, app: &AppInfo) {
         if let Err(e) = self.searcher.launch_app(&app.path) {
             eprintln!("Failed to launch app {}: {}", app.name, e);
         } else {
-            // Add to recent apps
-            self.searcher.add_to_recent(app.clone());
+            // Record the launch for recent apps and frecency ranking
+            self.searcher.add_to_recent(app);
+            
+            if self.config.ui.hide_on_launch {
+                self.dismiss(ctx);
+            }
+        }
+    }
+    
+    /// Carry out a command given on the command line or sent by a later invocation
+    pub fn handle_command(&mut self, ctx: &egui::Context, command: Command) {
+        match command {
+            Command::Show => self.set_visible(ctx, true),
+            Command::Hide => self.set_visible(ctx, false),
+            Command::Toggle => self.set_visible(ctx, !self.visible),
+            Command::Query(text) => {
+                self.search_query = text;
+                self.update_search();
+                self.set_visible(ctx, true);
+            }
+            Command::Quit => self.quit(ctx),
+        }
+    }
+    
+    /// Start drawing into a newly opened window
+    pub fn attach(&mut self, ctx: &egui::Context) {
+        // Commands arrive on another thread; wake the window to handle them
+        if let Some(server) = &self.ipc_server {
+            let ctx = ctx.clone();
+            server.set_waker(move || ctx.request_repaint());
+        }
+        self.in_background = false;
+        self.visible = true;
+        self.was_focused = false;
+    }
+    
+    /// Whether the window was closed to hide it, so the launcher should
+    /// `run_in_background` until it's needed again
+    pub fn closed_to_background(&self) -> bool {
+        self.in_background && !self.quitting
+    }
+    
+    /// Whether a daemon starting hidden should skip opening its window
+    pub fn starts_in_background(&self) -> bool {
+        self.hide_by_closing
+    }
+    
+    /// Serve commands and RPC calls with the window closed, keeping the loaded
+    /// index. Returns the command that needs the window again, or `None` to quit.
+    pub fn run_in_background(&mut self) -> Option<Command> {
+        let (wake, woken) = mpsc::channel();
+        self.ipc_server.as_ref()?.set_waker(move || {
+            let _ = wake.send(());
+        });
+        self.in_background = true;
+        self.visible = false;
+        
+        // Window changes made meanwhile, such as a new size, go nowhere; the
+        // next window is opened with the config as it is then
+        let ctx = egui::Context::default();
+        loop {
+            let _ = woken.recv_timeout(Duration::from_secs(1));
+            
+            if self.config_watcher.as_ref().is_some_and(|watcher| watcher.poll()) {
+                self.reload_config(&ctx);
+            }
+            self.searcher.poll_scan_progress();
             
-            // Optionally minimize or close the launcher after launching
-            // This would require additional egui context methods
+            let mut next = None;
+            let messages = self.ipc_server.as_ref().map(IpcServer::poll).unwrap_or_default();
+            for message in messages {
+                match message {
+                    Message::Command(command) if next.is_none() => match command {
+                        Command::Hide => {}
+                        Command::Quit => {
+                            self.quitting = true;
+                            next = Some(None);
+                        }
+                        Command::Toggle => next = Some(Some(Command::Show)),
+                        command => next = Some(Some(command)),
+                    },
+                    Message::Command(_) => {}
+                    Message::Rpc(call) => {
+                        let response = Rpc::handle(&call.message, &mut LauncherRpc { launcher: self, ctx: &ctx });
+                        call.reply(response);
+                    }
+                }
+            }
+            if let Some(next) = next {
+                return next;
+            }
         }
     }
+    
+    /// The config in use, e.g. to size the next window
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+    
+    fn set_visible(&mut self, ctx: &egui::Context, visible: bool) {
+        if !visible && self.hide_by_closing {
+            self.in_background = true;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        } else {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(visible));
+        }
+        if visible {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        } else if !self.search_query.is_empty() {
+            // The next time it's shown starts a fresh search
+            self.search_query.clear();
+            self.update_search();
+        }
+        self.visible = visible;
+        self.was_focused = false;
+    }
+    
+    /// Get the window out of the way: hidden in daemon mode, otherwise closed
+    fn dismiss(&mut self, ctx: &egui::Context) {
+        if self.daemon {
+            self.set_visible(ctx, false);
+        } else {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+    }
+    
+    fn quit(&mut self, ctx: &egui::Context) {
+        self.quitting = true;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+    }
+    
+    /// Apply edits to the config file. A file that doesn't parse or has invalid
+    /// settings is reported and the last good config stays in use.
+    fn reload_config(&mut self, ctx: &egui::Context) {
+        let config = match Config::try_load() {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Failed to reload config: {}", e);
+                self.config_error = Some(format!("Config not reloaded: {}", e));
+                return;
+            }
+        };
+        self.config_error = None;
+        
+        if config != self.config {
+            eprintln!("Config file changed, reloading");
+            self.apply_config(ctx, config);
+        }
+    }
+    
+    /// Change one setting through the dotted-path settings API and apply it
+    fn change_setting(&mut self, ctx: &egui::Context, path: &str, value: Value) {
+        let mut config = self.config.clone();
+        match config.set_setting(path, value) {
+            Ok(()) => {
+                self.apply_config(ctx, config);
+                self.setting_status = None;
+                if !self.unsaved_settings.iter().any(|unsaved| unsaved == path) {
+                    self.unsaved_settings.push(path.to_string());
+                }
+            }
+            Err(e) => self.setting_status = Some(e),
+        }
+    }
+    
+    /// Switch to `config`, redoing only the work its changes require: a rescan
+    /// for scan settings, a restyle for appearance and so on
+    fn apply_config(&mut self, ctx: &egui::Context, config: Config) {
+        if config.search != self.config.search {
+            self.searcher.apply_config(&config.search);
+        }
+        if config.appearance != self.config.appearance {
+            self.ui.set_appearance(&config.appearance);
+        }
+        if config.hotkeys != self.config.hotkeys {
+            self.ui.set_launch_shortcut(Self::shortcut(&config.hotkeys.launch_first));
+            self.clear_shortcut = Self::shortcut(&config.hotkeys.clear_search);
+        }
+        if (config.ui.window_width, config.ui.window_height) != (self.config.ui.window_width, self.config.ui.window_height) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(
+                config.ui.window_width,
+                config.ui.window_height,
+            )));
+        }
+        if config.ui.always_on_top != self.config.ui.always_on_top {
+            let level = if config.ui.always_on_top {
+                egui::WindowLevel::AlwaysOnTop
+            } else {
+                egui::WindowLevel::Normal
+            };
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
+        }
+        
+        self.config = config;
+        self.config_problems = self.config.validate();
+        self.update_search();
+    }
+    
+    /// Parse a hotkey setting such as "Ctrl+Space" into an egui shortcut
+    fn shortcut(spec: &str) -> Option<egui::KeyboardShortcut> {
+        let shortcut = LauncherUI::parse_shortcut(spec);
+        if shortcut.is_none() {
+            eprintln!("Ignoring invalid hotkey {:?}", spec);
+        }
+        shortcut
+    }
 }
 
 impl eframe::App for FadeLauncher {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Update animation time
-        self.animation_time += ctx.input(|i| i.unstable_dt);
+        self.animation_time += ctx.input(|i| i.unstable_dt) * self.config.ui.animation_speed;
         
-        // Check if we need to update search results
-        if self.last_search_time.elapsed() > Duration::from_millis(300) {
-            if !self.search_query.is_empty() || self.search_results.is_empty() {
-                self.update_search();
-            }
+        if self.config_watcher.as_ref().is_some_and(|watcher| watcher.poll()) {
+            self.reload_config(ctx);
         }
         
-        // Handle global shortcuts
-        ctx.input(|i| {
-            if i.key_pressed(egui::Key::Escape) {
-                if self.show_settings {
-                    self.show_settings = false;
-                } else if !self.search_query.is_empty() {
-                    self.search_query.clear();
-                    self.update_search();
-                } else {
-                    // Exit the application
-                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        let messages = self.ipc_server.as_ref().map(IpcServer::poll).unwrap_or_default();
+        for message in messages {
+            match message {
+                Message::Command(command) => self.handle_command(ctx, command),
+                Message::Rpc(call) => {
+                    let response = Rpc::handle(&call.message, &mut LauncherRpc { launcher: self, ctx });
+                    call.reply(response);
                 }
             }
-            
-            if i.modifiers.ctrl && i.key_pressed(egui::Key::Comma) {
-                self.show_settings = true;
-            }
+        }
+        
+        // Check if we need to update search results
+        if self.last_search_time.elapsed() > Duration::from_millis(300)
+            && (!self.search_query.is_empty() || self.search_results.is_empty())
+        {
+            self.update_search();
+        }
+        
+        // In daemon mode closing the window only hides it
+        if self.daemon && !self.quitting && !self.in_background && ctx.input(|i| i.viewport().close_requested()) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.dismiss(ctx);
+        }
+        
+        // So does clicking another window
+        let focused = ctx.input(|i| i.viewport().focused);
+        if self.daemon && self.visible && self.was_focused && focused == Some(false) {
+            self.dismiss(ctx);
+        }
+        self.was_focused = focused == Some(true);
+        
+        // Handle global shortcuts
+        let (clear_pressed, settings_pressed, quit_pressed) = ctx.input(|i| {
+            (
+                self.clear_shortcut
+                    .is_some_and(|shortcut| i.modifiers.matches_logically(shortcut.modifiers) && i.key_pressed(shortcut.logical_key)),
+                i.modifiers.ctrl && i.key_pressed(egui::Key::Comma),
+                i.modifiers.ctrl && i.key_pressed(egui::Key::Q),
+            )
         });
         
+        if clear_pressed {
+            if self.show_settings {
+                self.show_settings = false;
+            } else if !self.search_query.is_empty() {
+                self.search_query.clear();
+                self.update_search();
+            } else {
+                self.dismiss(ctx);
+            }
+        }
+        if settings_pressed {
+            self.show_settings = true;
+        }
+        if quit_pressed {
+            self.quit(ctx);
+        }
+        
+        // Refresh the results once indexing completes
+        let scan_progress = self.searcher.poll_scan_progress().clone();
+        if self.was_indexing && !scan_progress.running {
+            self.update_search();
+        }
+        self.was_indexing = scan_progress.running;
+        
         // Create the main window with custom styling
+        let mut app_to_launch = None;
         egui::CentralPanel::default()
             .frame(egui::Frame::none())
             .show(ctx, |ui| {
@@ -100,11 +390,34 @@ impl eframe::App for FadeLauncher {
                     ui,
                     &mut self.search_query,
                     &self.search_results,
+                    &scan_progress,
                     self.animation_time,
-                    |app| self.launch_app(app),
+                    |app| app_to_launch = Some(app.clone()),
                 );
             });
         
+        let notice = match (&self.config_error, self.config_problems.len()) {
+            (Some(error), _) => Some(error.clone()),
+            (None, 0) => None,
+            (None, 1) => Some(format!("Config problem: {}", self.config_problems[0])),
+            (None, count) => Some(format!("{} config problems, see Settings (Ctrl+,)", count)),
+        };
+        if let Some(notice) = notice {
+            egui::Area::new("config_error")
+                .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 12.0))
+                .show(ctx, |ui| {
+                    egui::Frame::popup(&ctx.style())
+                        .fill(FadeTheme::BACKGROUND_DARK)
+                        .show(ui, |ui| {
+                            ui.colored_label(FadeTheme::PINK_PRIMARY, notice);
+                        });
+                });
+        }
+        
+        if let Some(app) = app_to_launch {
+            self.launch_app(ctx, &app);
+        }
+        
         // Show settings window if needed
         if self.show_settings {
             egui::Window::new("Settings")
@@ -113,18 +426,196 @@ impl eframe::App for FadeLauncher {
                     ui.colored_label(FadeTheme::TEXT_PRIMARY, "Settings");
                     ui.separator();
                     
+                    let mut max_results = self.config.search.max_results;
+                    if ui.add(egui::Slider::new(&mut max_results, 1..=50).text("Max results")).changed() {
+                        self.change_setting(ctx, "search.max_results", max_results.into());
+                    }
+                    let mut hide_on_launch = self.config.ui.hide_on_launch;
+                    if ui.checkbox(&mut hide_on_launch, "Close after launching an app").changed() {
+                        self.change_setting(ctx, "ui.hide_on_launch", hide_on_launch.into());
+                    }
+                    
+                    // Any other setting by its path in the config file
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut self.setting_path).hint_text("appearance.transparency"));
+                        let current = self.config.get_setting(self.setting_path.trim()).map(|value| value.to_string());
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.setting_value)
+                                .hint_text(current.as_deref().unwrap_or("value")),
+                        );
+                        if ui.button("Set").clicked() {
+                            let path = self.setting_path.trim().to_string();
+                            let value = Config::parse_setting_value(self.setting_value.trim());
+                            self.change_setting(ctx, &path, value);
+                        }
+                    });
+                    if let Some(status) = &self.setting_status {
+                        ui.colored_label(FadeTheme::PINK_PRIMARY, status);
+                    }
+                    
+                    // Settings only editable in the config file can still be invalid
+                    self.config_problems = self.config.validate();
+                    if !self.config_problems.is_empty() {
+                        ui.separator();
+                        ui.colored_label(FadeTheme::PINK_PRIMARY, "Problems in the config file:");
+                        for problem in &self.config_problems {
+                            ui.label(problem.to_string());
+                        }
+                    }
+                    let unsupported = self.config.unsupported_settings();
+                    if !unsupported.is_empty() {
+                        ui.separator();
+                        ui.colored_label(FadeTheme::TEXT_SECONDARY, "Not supported yet, these settings have no effect:");
+                        for setting in &unsupported {
+                            ui.colored_label(FadeTheme::TEXT_MUTED, setting.to_string());
+                        }
+                    }
+                    
+                    ui.separator();
+                    
                     ui.colored_label(FadeTheme::TEXT_SECONDARY, "Scan directories:");
                     ui.label("Configure which directories to scan for applications");
                     
+                    ui.horizontal(|ui| {
+                        if scan_progress.running {
+                            ui.label(scan_progress.to_string());
+                        } else if scan_progress.files_seen > 0 {
+                            ui.label(format!(
+                                "{} applications indexed from {} files",
+                                scan_progress.apps_found, scan_progress.files_seen
+                            ));
+                        } else {
+                            ui.label(format!("{} applications indexed", scan_progress.apps_found));
+                        }
+                        
+                        // Restarting cancels a scan that is still running
+                        if ui.button("Rescan").clicked() {
+                            self.searcher.rescan();
+                        }
+                    });
+                    
                     ui.separator();
                     
-                    if ui.button("Close").clicked() {
-                        self.show_settings = false;
+                    // Debug view for include/exclude rules
+                    ui.colored_label(FadeTheme::TEXT_SECONDARY, "Why is a file hidden?");
+                    ui.add(egui::TextEdit::singleline(&mut self.filter_debug_path).hint_text("Full path of a file"));
+                    if !self.filter_debug_path.trim().is_empty() {
+                        let explanation = self.searcher.explain_filter(Path::new(self.filter_debug_path.trim()));
+                        ui.label(explanation);
                     }
+                    
+                    ui.separator();
+                    
+                    ui.horizontal(|ui| {
+                        // Saving over a file that failed to load would replace it with defaults
+                        let save = ui
+                            .add_enabled(self.config_error.is_none() && self.config_problems.is_empty(), egui::Button::new("Save"))
+                            .on_disabled_hover_text("Fix the problems in the config file first");
+                        if save.clicked() {
+                            // Only the changed settings are written, the rest of the file stays as it is
+                            match self.unsaved_settings.iter().try_for_each(|path| self.config.save_setting(path)) {
+                                Ok(()) => self.unsaved_settings.clear(),
+                                Err(e) => eprintln!("Failed to save config: {}", e),
+                            }
+                        }
+                        
+                        if ui.button("Close").clicked() {
+                            self.show_settings = false;
+                        }
+                        
+                        if self.daemon && ui.button("Quit").on_hover_text("Stop the background launcher (Ctrl+Q)").clicked() {
+                            self.quit(ctx);
+                        }
+                    });
                 });
         }
         
-        // Request repaint for smooth animations
-        ctx.request_repaint_after(Duration::from_millis(16)); // ~60 FPS
+        // Request repaint for smooth animations; while hidden, only keep an eye
+        // on the config watcher and scans
+        if self.visible {
+            ctx.request_repaint_after(Duration::from_millis(16)); // ~60 FPS
+        } else {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+    }
+}
+
+/// The window of a launcher that outlives it: a daemon that can't hide its
+/// window closes it instead, and opens a new one for the same launcher
+pub struct LauncherWindow(pub Rc<RefCell<FadeLauncher>>);
+
+impl eframe::App for LauncherWindow {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.0.borrow_mut().update(ctx, frame);
+    }
+}
+
+/// The launcher as JSON-RPC clients see it: the window's own searcher, history
+/// and config. Launching from a script leaves the window alone.
+struct LauncherRpc<'a> {
+    launcher: &'a mut FadeLauncher,
+    ctx: &'a egui::Context,
+}
+
+impl RpcTarget for LauncherRpc<'_> {
+    fn search(&self, query: &str, limit: Option<usize>) -> Vec<AppInfo> {
+        let limit = limit.unwrap_or(self.launcher.config.search.max_results);
+        self.launcher.searcher.search(query, limit)
+    }
+    
+    fn launch(&mut self, name_or_path: &str) -> Result<AppInfo, String> {
+        let searcher = &self.launcher.searcher;
+        let app = searcher
+            .resolve_app(name_or_path)
+            .ok_or_else(|| format!("No application matches {:?}", name_or_path))?;
+        
+        searcher
+            .launch_app(&app.path)
+            .map_err(|e| format!("Failed to launch app {}: {}", app.name, e))?;
+        searcher.add_to_recent(&app);
+        Ok(app)
+    }
+    
+    fn recent(&self, limit: Option<usize>) -> Vec<AppInfo> {
+        let limit = limit.unwrap_or(self.launcher.config.search.max_results);
+        self.launcher.searcher.get_recent_apps(limit)
+    }
+    
+    fn reload_config(&mut self) -> Result<(), String> {
+        self.launcher.reload_config(self.ctx);
+        match &self.launcher.config_error {
+            Some(error) => Err(error.clone()),
+            None => Ok(()),
+        }
+    }
+    
+    fn rescan(&mut self) {
+        self.launcher.searcher.rescan();
+    }
+    
+    fn get_setting(&self, path: &str) -> Result<Value, String> {
+        self.launcher.config.get_setting(path)
+    }
+    
+    fn set_setting(&mut self, path: &str, value: Value, save: bool) -> Result<(), String> {
+        // Saving over a file that failed to load would replace it with defaults
+        if save {
+            if let Some(error) = &self.launcher.config_error {
+                return Err(format!("Fix the config file first. {}", error));
+            }
+        }
+        
+        let mut config = self.launcher.config.clone();
+        config.set_setting(path, value)?;
+        self.launcher.apply_config(self.ctx, config);
+        
+        if save {
+            self.launcher.config.save_setting(path).map_err(|e| format!("Changed but not saved: {}", e))?;
+        }
+        Ok(())
+    }
+    
+    fn index_stats(&self) -> IndexStats {
+        self.launcher.searcher.index_stats()
     }
 }
\ No newline at end of file
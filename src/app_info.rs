@@ -0,0 +1,30 @@
+use crate::pe_version::VersionInfo;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// An indexed application, and a search result once scored
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AppInfo {
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub target: Option<String>, // Resolved target when `path` is a shortcut, symlink or single-command desktop entry
+    #[serde(default)]
+    pub sources: Vec<String>, // Every indexed file that launches this target, `path` included
+    #[serde(default)]
+    pub path_hint: Option<String>, // Location shown to tell apart apps with the same name
+    pub icon_path: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub hotkey: Option<String>, // Shortcut key assigned to a .lnk, e.g. "Ctrl+Alt+T"
+    #[serde(default)]
+    pub keywords: Vec<String>, // Extra search terms, e.g. GenericName and Keywords of .desktop entries
+    #[serde(default)]
+    pub version_info: Option<VersionInfo>, // Version resource of Windows executables
+    #[serde(skip)]
+    pub score: f32, // Relevance score for search results
+    #[serde(skip)]
+    pub name_matches: Vec<Range<usize>>, // Matched character ranges in `name`
+    #[serde(skip)]
+    pub path_matches: Vec<Range<usize>>, // Matched character ranges in `path`
+}
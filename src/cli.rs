@@ -0,0 +1,435 @@
+use crate::{app_info::AppInfo, config::Config, config_format::ConfigFormat, rpc::AppOutput, search::AppSearcher};
+use std::{fs, path::Path};
+
+/// Command-line subcommands that run without opening a window
+/// They use the same index, ranking and history as the launcher window.
+pub struct Cli;
+
+/// Flags shared by the subcommands that list apps
+#[derive(Debug, Default)]
+struct Options {
+    json: bool,
+    limit: Option<usize>,
+}
+
+impl Cli {
+    const USAGE: &'static str = "\
+Usage: fade-launcher [--show | --hide | --toggle | --query TEXT | --quit]
+       fade-launcher --daemon
+       fade-launcher COMMAND
+
+Without a command, the launcher window opens. If an instance is already
+running, the flags are sent to it instead: show or hide its window, show it
+searching for TEXT, or quit it.
+
+With --daemon the launcher starts hidden and stays in the background with its
+index loaded; closing the window only hides it until --quit or Ctrl+Q.
+
+Commands:
+  search QUERY [--json] [--limit N]
+                               Search the index the way the launcher window does
+  launch NAME|PATH             Launch an app and record it as recently used
+  recent [--json] [--limit N]  Recently launched apps
+  index rebuild                Rescan every root and rewrite the index cache
+  index stats [--json]         Size of the index
+  config check [FILE]          Check a config file (default: the installed one)
+  config get PATH              Print a setting, e.g. `config get search.max_results`
+  config set PATH VALUE        Change a setting and save
+  config add PATH VALUE        Append to a list setting, e.g. `config add search.scan_paths /opt/bin`
+  config remove PATH VALUE     Remove from a list setting
+  config convert FORMAT        Rewrite the installed config as toml, yaml or json
+  config convert INPUT OUTPUT  Convert a config file; formats follow the extensions
+
+VALUE is JSON; anything that doesn't parse as JSON is taken as a string.";
+
+    /// Run the subcommand in `args` (program name excluded), returning the exit code
+    pub fn run(args: &[String]) -> i32 {
+        let (args, options) = match Self::parse_options(args) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("{}\n\n{}", e, Self::USAGE);
+                return 2;
+            }
+        };
+
+        match args.as_slice() {
+            ["search", query @ ..] if !query.is_empty() => Self::search(&query.join(" "), &options),
+            ["launch", name_or_path] => Self::launch(name_or_path),
+            ["recent"] => Self::recent(&options),
+            ["index", "rebuild"] => Self::index_rebuild(),
+            ["index", "stats"] => Self::index_stats(&options),
+            ["config", "check"] => Self::config_check(None),
+            ["config", "check", file] => Self::config_check(Some(Path::new(file))),
+            ["config", "get", path] => Self::config_get(path),
+            ["config", "convert", format] => Self::config_convert(format),
+            ["config", "convert", input, output] => Self::config_convert_file(Path::new(input), Path::new(output)),
+            ["config", action @ ("set" | "add" | "remove"), path, value] => Self::config_edit(action, path, value),
+            ["help" | "--help" | "-h"] => {
+                println!("{}", Self::USAGE);
+                0
+            }
+            _ => {
+                eprintln!("{}", Self::USAGE);
+                2
+            }
+        }
+    }
+
+    /// Split `--json` and `--limit N` from the positional arguments
+    fn parse_options(args: &[String]) -> Result<(Vec<&str>, Options), String> {
+        let mut options = Options::default();
+        let mut positional = Vec::new();
+        let mut args = args.iter().map(String::as_str);
+
+        while let Some(arg) = args.next() {
+            match arg {
+                "--json" => options.json = true,
+                "--limit" => {
+                    let limit = args.next().and_then(|limit| limit.parse().ok());
+                    options.limit = Some(limit.ok_or("--limit needs a number")?);
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        Ok((positional, options))
+    }
+
+    fn search(query: &str, options: &Options) -> i32 {
+        let Some((config, searcher)) = Self::load_searcher() else {
+            return 1;
+        };
+
+        let results = searcher.search(query, options.limit.unwrap_or(config.search.max_results));
+        Self::print_apps(&results, options);
+        if results.is_empty() {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn launch(name_or_path: &str) -> i32 {
+        let Some((_, searcher)) = Self::load_searcher() else {
+            return 1;
+        };
+        let Some(app) = searcher.resolve_app(name_or_path) else {
+            eprintln!("No application matches {:?}", name_or_path);
+            return 1;
+        };
+
+        match searcher.launch_app(&app.path) {
+            Ok(()) => {
+                searcher.add_to_recent(&app);
+                0
+            }
+            Err(e) => {
+                eprintln!("Failed to launch app {}: {}", app.name, e);
+                1
+            }
+        }
+    }
+
+    fn recent(options: &Options) -> i32 {
+        let Some((config, searcher)) = Self::load_searcher() else {
+            return 1;
+        };
+
+        let recent = searcher.get_recent_apps(options.limit.unwrap_or(config.search.max_results));
+        Self::print_apps(&recent, options);
+        0
+    }
+
+    fn index_rebuild() -> i32 {
+        let Some(config) = Self::load_config() else {
+            return 1;
+        };
+
+        let mut searcher = AppSearcher::from_config(&config.search);
+        searcher.rebuild_index();
+        println!("{}", searcher.index_stats());
+        0
+    }
+
+    fn index_stats(options: &Options) -> i32 {
+        let Some((_, searcher)) = Self::load_searcher() else {
+            return 1;
+        };
+
+        let stats = searcher.index_stats();
+        if options.json {
+            println!("{}", serde_json::to_string_pretty(&stats).unwrap_or_default());
+        } else {
+            println!("{}", stats);
+        }
+        0
+    }
+
+    /// A searcher over the current index, scanning first if the cache is stale
+    fn load_searcher() -> Option<(Config, AppSearcher)> {
+        let config = Self::load_config()?;
+        let mut searcher = AppSearcher::from_config(&config.search);
+        searcher.load_index();
+        Some((config, searcher))
+    }
+
+    /// One app per line as `name<TAB>path`, or a JSON array
+    fn print_apps(apps: &[AppInfo], options: &Options) {
+        if options.json {
+            let output: Vec<AppOutput> = apps.iter().map(AppOutput::from).collect();
+            println!("{}", serde_json::to_string_pretty(&output).unwrap_or_default());
+        } else {
+            for app in apps {
+                println!("{}\t{}", app.name, app.path);
+            }
+        }
+    }
+
+    /// Parse and validate a config file, printing every problem. Exits with 1 if
+    /// there are any, so it can run in dotfile CI.
+    fn config_check(file: Option<&Path>) -> i32 {
+        let Some(path) = file.map(Path::to_path_buf).or_else(Config::get_config_path) else {
+            eprintln!("No config directory on this system");
+            return 1;
+        };
+        if !path.exists() {
+            println!("{}: no config file, the defaults are used", path.display());
+            return 0;
+        }
+
+        let (config, unknown) = match Config::load_file_with_unknown(&path) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                eprintln!("{}", e);
+                return 1;
+            }
+        };
+
+        // Unknown settings are usually typos, so they fail the check too
+        let mut problems = unknown;
+        problems.extend(config.validate());
+        for problem in &problems {
+            eprintln!("{}: {}", path.display(), problem);
+        }
+        // Worth knowing about, but not a reason to fail
+        for unsupported in config.unsupported_settings() {
+            eprintln!("{}: warning: {}", path.display(), unsupported);
+        }
+
+        if problems.is_empty() {
+            println!("{}: OK", path.display());
+            0
+        } else {
+            1
+        }
+    }
+
+    fn config_get(path: &str) -> i32 {
+        let Some(config) = Self::load_config_unchecked() else {
+            return 1;
+        };
+
+        match config.get_setting(path) {
+            Ok(value) => {
+                println!("{}", serde_json::to_string_pretty(&value).unwrap_or_default());
+                0
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                1
+            }
+        }
+    }
+
+    fn config_edit(action: &str, path: &str, value: &str) -> i32 {
+        let Some(mut config) = Self::load_config_unchecked() else {
+            return 1;
+        };
+
+        let value = Config::parse_setting_value(value);
+        let result = match action {
+            "set" => config.set_setting(path, value),
+            "add" => config.add_setting(path, value),
+            _ => config.remove_setting(path, &value),
+        };
+
+        Self::exit_code(result.map_err(|e| e.into()).and_then(|()| config.save_setting(path)))
+    }
+
+    /// Rewrite the installed config in another format. The old file is renamed to
+    /// `*.bak` so the new one is used whatever the precedence.
+    fn config_convert(format: &str) -> i32 {
+        let Some(format) = ConfigFormat::from_name(format) else {
+            eprintln!("Unknown config format {:?}, expected toml, yaml or json", format);
+            return 2;
+        };
+        let (Some(source), Some(dir)) = (Config::get_config_path(), Config::get_config_dir()) else {
+            eprintln!("No config directory on this system");
+            return 1;
+        };
+        let target = dir.join(format.file_name());
+        if source == target {
+            println!("{} is already {}", source.display(), format);
+            return 0;
+        }
+        let Some(config) = Self::load_config() else {
+            return 1;
+        };
+
+        Self::exit_code((|| {
+            fs::create_dir_all(&dir)?;
+            fs::write(&target, config.to_string_as(format, None)?)?;
+            if source.exists() {
+                let backup = format!("{}.bak", source.display());
+                fs::rename(&source, &backup)?;
+                println!("Moved {} to {}", source.display(), backup);
+            }
+            println!("Wrote {}", target.display());
+            Ok(())
+        })())
+    }
+
+    /// Convert any config file, e.g. one kept with dotfiles. An existing TOML
+    /// output keeps its comments.
+    fn config_convert_file(input: &Path, output: &Path) -> i32 {
+        let Some(format) = ConfigFormat::from_path(output) else {
+            eprintln!("Can't tell the format of {}, use a .toml, .yaml or .json file", output.display());
+            return 2;
+        };
+
+        Self::exit_code((|| {
+            let config = Config::load_file(input)?;
+            let existing = fs::read_to_string(output).ok();
+            fs::write(output, config.to_string_as(format, existing.as_deref())?)?;
+            println!("Wrote {}", output.display());
+            Ok(())
+        })())
+    }
+
+    /// 0 on success, otherwise the error is printed and the exit code is 1
+    fn exit_code(result: Result<(), Box<dyn std::error::Error>>) -> i32 {
+        match result {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("{}", e);
+                1
+            }
+        }
+    }
+
+    /// The installed config; a broken file is reported rather than replaced
+    fn load_config() -> Option<Config> {
+        Config::try_load().map_err(|e| eprintln!("{}", e)).ok()
+    }
+
+    /// The installed config even if some settings are invalid, so `config set`
+    /// can fix them
+    fn load_config_unchecked() -> Option<Config> {
+        Config::try_load_unchecked().map_err(|e| eprintln!("{}", e)).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    /// Fresh, empty directory for one test
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("fade-launcher-cli-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn options_are_accepted_anywhere() {
+        let given = args(&["--json", "search", "fire", "--limit", "3", "fox"]);
+        let (positional, options) = Cli::parse_options(&given).unwrap();
+        assert_eq!(positional, ["search", "fire", "fox"]);
+        assert!(options.json);
+        assert_eq!(options.limit, Some(3));
+
+        let given = args(&["recent", "--json"]);
+        let (positional, options) = Cli::parse_options(&given).unwrap();
+        assert_eq!(positional, ["recent"]);
+        assert!(options.json);
+        assert_eq!(options.limit, None);
+    }
+
+    #[test]
+    fn limit_needs_a_number() {
+        for given in [args(&["search", "fire", "--limit"]), args(&["search", "--limit", "many", "fire"])] {
+            assert_eq!(Cli::parse_options(&given).unwrap_err(), "--limit needs a number");
+            assert_eq!(Cli::run(&given), 2);
+        }
+    }
+
+    #[test]
+    fn unknown_commands_print_usage() {
+        assert_eq!(Cli::run(&args(&["config", "set", "a", "b", "c"])), 2);
+        assert_eq!(Cli::run(&args(&["config", "set", "a"])), 2);
+        assert_eq!(Cli::run(&args(&["search"])), 2);
+        assert_eq!(Cli::run(&args(&["frobnicate"])), 2);
+        assert_eq!(Cli::run(&args(&["--help"])), 0);
+    }
+
+    #[test]
+    fn config_check_fails_on_problems() {
+        let dir = temp_dir("check");
+
+        let valid = dir.join("valid.toml");
+        fs::write(&valid, "[search]\nmax_results = 5\n").unwrap();
+        assert_eq!(Cli::config_check(Some(&valid)), 0);
+
+        let invalid = dir.join("invalid.json");
+        fs::write(&invalid, r#"{ "search": { "max_results": 0 } }"#).unwrap();
+        assert_eq!(Cli::config_check(Some(&invalid)), 1);
+
+        let unknown = dir.join("unknown.yaml");
+        fs::write(&unknown, "search:\n  max_result: 5\n").unwrap();
+        assert_eq!(Cli::config_check(Some(&unknown)), 1);
+
+        let broken = dir.join("broken.toml");
+        fs::write(&broken, "[search\n").unwrap();
+        assert_eq!(Cli::config_check(Some(&broken)), 1);
+
+        // Settings without effect only warn
+        let unsupported = dir.join("unsupported.toml");
+        fs::write(&unsupported, "[appearance]\nblur_background = true\n").unwrap();
+        assert_eq!(Cli::config_check(Some(&unsupported)), 0);
+
+        // Without a file the defaults are used, which are fine
+        assert_eq!(Cli::config_check(Some(&dir.join("missing.toml"))), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn converts_config_files_between_formats() {
+        let dir = temp_dir("convert");
+        let input = dir.join("config.json");
+        fs::write(&input, r#"{ "search": { "max_results": 7 } }"#).unwrap();
+
+        let yaml = dir.join("config.yaml");
+        assert_eq!(Cli::config_convert_file(&input, &yaml), 0);
+        assert_eq!(Config::load_file(&yaml).unwrap().search.max_results, 7);
+
+        // An existing TOML output keeps its comments
+        let toml = dir.join("config.toml");
+        fs::write(&toml, "# Tuned for the laptop\n[search]\nmax_results = 3 # fewer fit\n").unwrap();
+        assert_eq!(Cli::config_convert_file(&yaml, &toml), 0);
+        let written = fs::read_to_string(&toml).unwrap();
+        assert!(written.contains("# Tuned for the laptop\n[search]\n"), "{}", written);
+        assert!(written.contains("max_results = 7 # fewer fit"), "{}", written);
+
+        assert_eq!(Cli::config_convert_file(&input, &dir.join("config.ini")), 2);
+        assert_eq!(Cli::config_convert_file(&dir.join("missing.json"), &dir.join("out.json")), 1);
+        assert!(!dir.join("out.json").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,15 +1,30 @@
+use crate::{
+    config_format::ConfigFormat,
+    filter_rules::{FilterRule, RuleKind, RuleTarget},
+    hotkey::Hotkey,
+};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf};
+use serde_json::Value;
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Settings from the config file
+/// Missing fields and sections take their default values, so a file only needs
+/// the settings it changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
+    pub schema_version: u32, // Layout of the file, see `Config::MIGRATIONS`
     pub ui: UiConfig,
     pub search: SearchConfig,
     pub hotkeys: HotkeyConfig,
     pub appearance: AppearanceConfig,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UiConfig {
     pub window_width: f32,
     pub window_height: f32,
@@ -18,34 +33,108 @@ pub struct UiConfig {
     pub animation_speed: f32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SearchConfig {
     pub max_results: usize,
-    pub scan_paths: Vec<PathBuf>,
+    pub scan_paths: Vec<ScanRoot>,
     pub file_extensions: Vec<String>,
-    pub exclude_patterns: Vec<String>,
+    pub include_patterns: Vec<FilterRule>, // Keep matching apps even if an exclude rule matches
+    pub exclude_patterns: Vec<FilterRule>,
     pub enable_fuzzy_search: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A directory scanned for applications and how to walk it
+/// A plain path string in the config uses the defaults for everything else.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ScanRootSpec")]
+pub struct ScanRoot {
+    pub path: PathBuf,
+    pub max_depth: usize, // 1 only indexes files directly inside `path`
+    pub follow_links: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<String>>, // Replaces `file_extensions` for this root
+    pub include_hidden: bool, // Index dot-files and walk dot-directories
+    pub priority_boost: f32, // Added to the search score of apps found here
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScanRootSpec {
+    Path(PathBuf),
+    Root {
+        path: PathBuf,
+        #[serde(default = "ScanRoot::default_max_depth")]
+        max_depth: usize,
+        #[serde(default)]
+        follow_links: bool,
+        #[serde(default)]
+        extensions: Option<Vec<String>>,
+        #[serde(default)]
+        include_hidden: bool,
+        #[serde(default)]
+        priority_boost: f32,
+    },
+}
+
+impl From<ScanRootSpec> for ScanRoot {
+    fn from(spec: ScanRootSpec) -> Self {
+        match spec {
+            ScanRootSpec::Path(path) => Self::new(path),
+            ScanRootSpec::Root {
+                path,
+                max_depth,
+                follow_links,
+                extensions,
+                include_hidden,
+                priority_boost,
+            } => Self {
+                path,
+                max_depth,
+                follow_links,
+                extensions,
+                include_hidden,
+                priority_boost,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct HotkeyConfig {
-    pub toggle_launcher: String,
+    pub toggle_launcher: String, // Global hotkey, not supported yet; empty when unset
     pub clear_search: String,
     pub launch_first: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppearanceConfig {
-    pub theme_variant: String, // "fade", "neon", "minimal", etc.
+    pub theme_variant: String, // Only "fade" so far
     pub transparency: f32,
-    pub blur_background: bool,
+    pub blur_background: bool, // Not supported yet
     pub show_particles: bool,
     pub gradient_animation: bool,
 }
 
+/// A setting that parses but can't be used, found by [`Config::validate`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigProblem {
+    pub path: String, // Dotted path of the field, e.g. `search.scan_paths[2].max_depth`
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
+            schema_version: Self::SCHEMA_VERSION,
             ui: UiConfig::default(),
             search: SearchConfig::default(),
             hotkeys: HotkeyConfig::default(),
@@ -71,16 +160,28 @@ impl Default for SearchConfig {
         Self {
             max_results: 10,
             scan_paths: Vec::new(), // Will be populated from system defaults
-            file_extensions: vec![
-                ".exe".to_string(),
-                ".lnk".to_string(),
-                ".bat".to_string(),
-                ".cmd".to_string(),
-            ],
+            file_extensions: Vec::new(), // Will be populated from system defaults
+            include_patterns: Vec::new(),
             exclude_patterns: vec![
-                "unins".to_string(),
-                "setup".to_string(),
-                "install".to_string(),
+                FilterRule::substring("unins"),
+                FilterRule::substring("setup"),
+                FilterRule::substring("install"),
+                // Whole words only, so apps like "Daemon Tools" stay
+                FilterRule {
+                    pattern: "* helper".to_string(),
+                    kind: RuleKind::Glob,
+                    target: RuleTarget::Name,
+                },
+                FilterRule {
+                    pattern: "* daemon".to_string(),
+                    kind: RuleKind::Glob,
+                    target: RuleTarget::Name,
+                },
+                FilterRule {
+                    pattern: "**crash*reporter*".to_string(),
+                    kind: RuleKind::Glob,
+                    target: RuleTarget::Path,
+                },
             ],
             enable_fuzzy_search: true,
         }
@@ -90,7 +191,7 @@ impl Default for SearchConfig {
 impl Default for HotkeyConfig {
     fn default() -> Self {
         Self {
-            toggle_launcher: "Ctrl+Space".to_string(),
+            toggle_launcher: String::new(),
             clear_search: "Escape".to_string(),
             launch_first: "Enter".to_string(),
         }
@@ -102,79 +203,710 @@ impl Default for AppearanceConfig {
         Self {
             theme_variant: "fade".to_string(),
             transparency: 0.95,
-            blur_background: true,
+            blur_background: false,
             show_particles: true,
             gradient_animation: true,
         }
     }
 }
 
+impl ScanRoot {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            max_depth: Self::default_max_depth(),
+            follow_links: false,
+            extensions: None,
+            include_hidden: false,
+            priority_boost: 0.0,
+        }
+    }
+    
+    /// Root whose subdirectories aren't walked, e.g. a PATH directory
+    pub fn shallow(path: PathBuf) -> Self {
+        Self {
+            max_depth: 1,
+            ..Self::new(path)
+        }
+    }
+    
+    fn default_max_depth() -> usize {
+        3
+    }
+    
+    /// Extensions indexed below this root
+    pub fn extensions<'a>(&'a self, default: &'a [String]) -> &'a [String] {
+        self.extensions.as_deref().unwrap_or(default)
+    }
+    
+    /// Depth of `path` below this root (the root itself is 0), if it is inside it
+    pub fn depth_of(&self, path: &Path) -> Option<usize> {
+        let relative = path.strip_prefix(&self.path).ok()?;
+        Some(relative.components().count())
+    }
+    
+    /// Whether `path` lies inside this root and should be indexed: within the depth
+    /// limit and, unless hidden files are included, not below a dot-file
+    pub fn covers(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.path) else {
+            return false;
+        };
+        
+        relative.components().count() <= self.max_depth
+            && (self.include_hidden
+                || !relative.components().any(|c| c.as_os_str().to_string_lossy().starts_with('.')))
+    }
+    
+    /// The most specific root in `roots` containing `path`
+    pub fn closest<'a>(roots: &'a [ScanRoot], path: &Path) -> Option<&'a ScanRoot> {
+        roots
+            .iter()
+            .filter_map(|root| root.depth_of(path).map(|depth| (root, depth)))
+            .min_by_key(|(_, depth)| *depth)
+            .map(|(root, _)| root)
+    }
+}
+
 impl Config {
-    /// Load configuration from file or create default
-    pub fn load() -> Self {
-        if let Some(config_path) = Self::get_config_path() {
-            if config_path.exists() {
-                if let Ok(content) = fs::read_to_string(&config_path) {
-                    if let Ok(config) = serde_json::from_str(&content) {
-                        return config;
+    /// Schema version written by this build
+    pub const SCHEMA_VERSION: u32 = Self::MIGRATIONS.len() as u32 + 1;
+    
+    /// Values `appearance.theme_variant` accepts, one per palette in the theme
+    pub const THEME_VARIANTS: &'static [&'static str] = &["fade"];
+    
+    /// Upgrades a file from one schema version to the next; the first entry
+    /// upgrades version 1 (files without `schema_version`) to version 2
+    const MIGRATIONS: &'static [fn(&mut Value)] = &[Self::migrate_v1];
+    
+    /// Load configuration from file, or the defaults if there is no file.
+    /// Files with an older schema are migrated. A file that can't be read or
+    /// parsed is an error, reported with its line and column, and so is one with
+    /// settings that fail validation, such as `max_results: 0`.
+    pub fn try_load() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_installed(true)
+    }
+    
+    /// Like [`Config::try_load`], but a file with invalid settings is returned as
+    /// it is, so they can be fixed one setting at a time
+    pub fn try_load_unchecked() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_installed(false)
+    }
+    
+    fn load_installed(check: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        match Self::get_config_path() {
+            Some(config_path) if config_path.exists() => {
+                for ignored in Self::config_file_candidates().iter().filter(|path| path.exists() && **path != config_path) {
+                    eprintln!("Ignoring {:?}, {:?} takes precedence", ignored, config_path);
+                }
+                Self::load_installed_file(&config_path, check)
+            }
+            _ => Ok(Config::default()),
+        }
+    }
+    
+    /// Load the installed config file at `config_path`, migrating it in place
+    fn load_installed_file(config_path: &Path, check: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        let (config, unknown) = Self::load_file_with_unknown(config_path)?;
+        for unknown in unknown {
+            eprintln!("Ignoring config setting: {}", unknown);
+        }
+        if let Err(e) = Self::upgrade_file(config_path) {
+            eprintln!("Failed to write the migrated config to {:?}: {}", config_path, e);
+        }
+        if check {
+            config.ensure_valid().map_err(|e| format!("{}: {}", config_path.display(), e))?;
+        }
+        for unsupported in config.unsupported_settings() {
+            eprintln!("Config setting has no effect: {}", unsupported);
+        }
+        Ok(config)
+    }
+    
+    /// Load a config file from anywhere, e.g. one kept with dotfiles. The format
+    /// follows the extension; unknown extensions are read as JSON.
+    pub fn load_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_file_with_unknown(path).map(|(config, _)| config)
+    }
+    
+    /// Like [`Config::load_file`], also returning the settings no field reads
+    pub fn load_file_with_unknown(path: &Path) -> Result<(Self, Vec<ConfigProblem>), Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        let format = ConfigFormat::from_path(path).unwrap_or(ConfigFormat::Json);
+        Self::parse_with_unknown(&content, format).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+    
+    /// Parse the contents of a config file, migrating it to the current schema
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self, String> {
+        Self::parse_with_unknown(content, format).map(|(config, _)| config)
+    }
+    
+    /// Like [`Config::parse`], also returning the settings no field reads, such
+    /// as misspelled ones. Missing settings take their defaults, so these would
+    /// otherwise be dropped without a word.
+    pub fn parse_with_unknown(content: &str, format: ConfigFormat) -> Result<(Self, Vec<ConfigProblem>), String> {
+        let mut value = match format {
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| Self::describe_error(&e))?,
+            _ => format.parse(content)?,
+        };
+        
+        let version = Self::schema_version_of(&value)?;
+        Self::migrate(&mut value, version);
+        
+        let config = Self::from_value(value.clone(), content, format, version)?;
+        let mut unknown = Vec::new();
+        Self::collect_unknown(&value, &config.to_json()?, "", &mut unknown);
+        Ok((config, unknown))
+    }
+    
+    fn from_value(value: Value, content: &str, format: ConfigFormat, version: u32) -> Result<Self, String> {
+        if version == Self::SCHEMA_VERSION && format == ConfigFormat::Json {
+            // Parse the text again so type errors point at a line and column
+            return serde_json::from_str(content).map_err(|e| Self::describe_error(&e));
+        }
+        
+        serde_json::from_value(value).map_err(|e| {
+            // Values have no positions; most problems are in the original text too
+            match format {
+                ConfigFormat::Json => match serde_json::from_str::<Self>(content) {
+                    Err(original) => Self::describe_error(&original),
+                    Ok(_) => format!("{} (after migrating from schema version {})", e, version),
+                },
+                _ => e.to_string(),
+            }
+        })
+    }
+    
+    /// Adds the keys of `file` that aren't in `known`, the parsed config written
+    /// out in full, below `path`
+    fn collect_unknown(file: &Value, known: &Value, path: &str, unknown: &mut Vec<ConfigProblem>) {
+        match (file, known) {
+            (Value::Object(file), Value::Object(known)) => {
+                for (key, value) in file {
+                    let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    match known.get(key) {
+                        Some(known) => Self::collect_unknown(value, known, &child, unknown),
+                        // Unset optional fields, such as a root's extensions, aren't written
+                        None if value.is_null() => {}
+                        None => unknown.push(ConfigProblem {
+                            path: child,
+                            message: "unknown setting, it has no effect".to_string(),
+                        }),
                     }
                 }
             }
+            (Value::Array(file), Value::Array(known)) => {
+                for (i, (value, known)) in file.iter().zip(known).enumerate() {
+                    Self::collect_unknown(value, known, &format!("{}[{}]", path, i), unknown);
+                }
+            }
+            _ => {}
         }
+    }
+    
+    fn describe_error(e: &serde_json::Error) -> String {
+        // serde_json appends the position to its message; lead with it instead
+        let message = e.to_string();
+        let message = message
+            .rsplit_once(" at line ")
+            .map_or(message.as_str(), |(message, _)| message);
+        format!("line {}, column {}: {}", e.line(), e.column(), message)
+    }
+    
+    /// Schema version of a parsed file; files without `schema_version` are version 1
+    fn schema_version_of(value: &Value) -> Result<u32, String> {
+        let version = match value.get("schema_version") {
+            None => 1,
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .filter(|version| *version >= 1)
+                .ok_or_else(|| format!("invalid schema_version {}", version))?,
+        };
+        
+        if version > Self::SCHEMA_VERSION {
+            return Err(format!(
+                "schema_version {} is newer than this version of fade-launcher supports ({})",
+                version,
+                Self::SCHEMA_VERSION
+            ));
+        }
+        Ok(version)
+    }
+    
+    /// Upgrade a parsed file from `version` to the current schema. Returns whether
+    /// a migration changed any setting.
+    fn migrate(value: &mut Value, version: u32) -> bool {
+        if version >= Self::SCHEMA_VERSION {
+            return false;
+        }
+        
+        let original = value.clone();
+        for migrate in &Self::MIGRATIONS[version as usize - 1..] {
+            migrate(value);
+        }
+        let changed = *value != original;
         
-        // Return default config if loading fails
-        Config::default()
+        if let Some(object) = value.as_object_mut() {
+            object.insert("schema_version".to_string(), Self::SCHEMA_VERSION.into());
+        }
+        changed
+    }
+    
+    /// Write migrated settings back to the config file, so an old file is only
+    /// migrated once. Files the migrations leave as they are, such as partial
+    /// hand-written ones without `schema_version`, aren't touched.
+    fn upgrade_file(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        let format = ConfigFormat::from_path(path).unwrap_or(ConfigFormat::Json);
+        let mut value = format.parse(&content)?;
+        let version = Self::schema_version_of(&value)?;
+        
+        if Self::migrate(&mut value, version) {
+            fs::write(path, format.write(&value, Some(&content))?)?;
+            eprintln!("Migrated {:?} from schema version {} to {}", path, version, Self::SCHEMA_VERSION);
+        }
+        Ok(())
     }
     
-    /// Save configuration to file
+    /// Version 1 saved the Windows extension list as the default for every
+    /// platform. That list becomes empty so the platform defaults apply, but only
+    /// in files version 1 wrote itself: it couldn't read partial files, so those
+    /// have every section and search setting. A list written by hand is kept.
+    fn migrate_v1(config: &mut Value) {
+        const OLD_DEFAULT_EXTENSIONS: [&str; 4] = [".exe", ".lnk", ".bat", ".cmd"];
+        const SECTIONS: [&str; 4] = ["ui", "search", "hotkeys", "appearance"];
+        const SEARCH_FIELDS: [&str; 5] = ["max_results", "scan_paths", "file_extensions", "exclude_patterns", "enable_fuzzy_search"];
+        
+        let written_by_v1 = SECTIONS.iter().all(|section| config.get(section).is_some_and(Value::is_object))
+            && SEARCH_FIELDS.iter().all(|field| config["search"].get(field).is_some());
+        if !written_by_v1 {
+            return;
+        }
+        
+        let Some(extensions) = config.pointer_mut("/search/file_extensions") else {
+            return;
+        };
+        if *extensions == Value::from(OLD_DEFAULT_EXTENSIONS.to_vec()) {
+            *extensions = Value::Array(Vec::new());
+        }
+    }
+    
+    /// Save configuration to file. Settings that fail validation aren't saved.
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(config_path) = Self::get_config_path() {
-            // Create directory if it doesn't exist
-            if let Some(parent) = config_path.parent() {
-                fs::create_dir_all(parent)?;
-            }
-            
-            let content = serde_json::to_string_pretty(self)?;
-            fs::write(config_path, content)?;
+        match Self::get_config_path() {
+            Some(config_path) => self.save_to(&config_path),
+            None => Ok(()),
+        }
+    }
+    
+    /// Save configuration to `config_path`, in the format its extension names
+    pub fn save_to(&self, config_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_valid()?;
+        
+        // Create directory if it doesn't exist
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
         }
         
+        let format = ConfigFormat::from_path(config_path).unwrap_or(ConfigFormat::Json);
+        let existing = fs::read_to_string(config_path).ok();
+        let content = self.to_string_as(format, existing.as_deref())?;
+        fs::write(config_path, content)?;
+        
         Ok(())
     }
     
-    /// Get the configuration file path
-    fn get_config_path() -> Option<PathBuf> {
-        dirs::config_dir().map(|dir| dir.join("fade-launcher").join("config.json"))
+    /// Fails with every validation problem, so nothing invalid gets saved
+    pub(crate) fn ensure_valid(&self) -> Result<(), String> {
+        let problems = self.validate();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        
+        let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        Err(format!("invalid settings: {}", problems.join("; ")))
     }
     
-    /// Update a specific configuration value
-    pub fn update_ui_setting(&mut self, setting: &str, value: serde_json::Value) -> Result<(), String> {
-        match setting {
-            "window_width" => {
-                if let Some(val) = value.as_f64() {
-                    self.ui.window_width = val as f32;
-                } else {
-                    return Err("Invalid value for window_width".to_string());
-                }
+    /// Settings that parse but make no sense, such as a zero window width or an
+    /// unknown hotkey, each named by its field path
+    pub fn validate(&self) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+        let mut problem = |path: String, message: &str| {
+            problems.push(ConfigProblem {
+                path,
+                message: message.to_string(),
+            })
+        };
+        
+        if self.schema_version != Self::SCHEMA_VERSION {
+            problem("schema_version".to_string(), &format!("must be {}", Self::SCHEMA_VERSION));
+        }
+        
+        let ui = &self.ui;
+        for (field, size) in [("window_width", ui.window_width), ("window_height", ui.window_height)] {
+            if !(size.is_finite() && size > 0.0) {
+                problem(format!("ui.{}", field), "must be a positive number of pixels");
+            }
+        }
+        if !(ui.animation_speed.is_finite() && ui.animation_speed >= 0.0) {
+            problem("ui.animation_speed".to_string(), "must be zero or more");
+        }
+        
+        let search = &self.search;
+        if search.max_results == 0 {
+            problem("search.max_results".to_string(), "must be at least 1");
+        }
+        for (i, root) in search.scan_paths.iter().enumerate() {
+            if root.path.as_os_str().is_empty() {
+                problem(format!("search.scan_paths[{}].path", i), "must not be empty");
             }
-            "window_height" => {
-                if let Some(val) = value.as_f64() {
-                    self.ui.window_height = val as f32;
-                } else {
-                    return Err("Invalid value for window_height".to_string());
+            if root.max_depth == 0 {
+                problem(format!("search.scan_paths[{}].max_depth", i), "must be at least 1");
+            }
+            if !root.priority_boost.is_finite() {
+                problem(format!("search.scan_paths[{}].priority_boost", i), "must be a finite number");
+            }
+            for (j, extension) in root.extensions.iter().flatten().enumerate() {
+                if !extension.starts_with('.') {
+                    problem(format!("search.scan_paths[{}].extensions[{}]", i, j), "must start with a dot, e.g. \".exe\"");
                 }
             }
-            "always_on_top" => {
-                if let Some(val) = value.as_bool() {
-                    self.ui.always_on_top = val;
-                } else {
-                    return Err("Invalid value for always_on_top".to_string());
+        }
+        for (i, extension) in search.file_extensions.iter().enumerate() {
+            if !extension.starts_with('.') {
+                problem(format!("search.file_extensions[{}]", i), "must start with a dot, e.g. \".exe\"");
+            }
+        }
+        for (field, rules) in [("include_patterns", &search.include_patterns), ("exclude_patterns", &search.exclude_patterns)] {
+            for (i, rule) in rules.iter().enumerate() {
+                if rule.pattern.is_empty() {
+                    problem(format!("search.{}[{}].pattern", field, i), "must not be empty");
+                } else if let Err(e) = rule.check() {
+                    problem(format!("search.{}[{}].pattern", field, i), &format!("invalid pattern: {}", e));
                 }
             }
-            _ => {
-                return Err(format!("Unknown setting: {}", setting));
+        }
+        
+        let hotkeys = &self.hotkeys;
+        for (field, spec) in [
+            ("toggle_launcher", &hotkeys.toggle_launcher),
+            ("clear_search", &hotkeys.clear_search),
+            ("launch_first", &hotkeys.launch_first),
+        ] {
+            let unset = field == "toggle_launcher" && spec.is_empty();
+            if !unset && Hotkey::parse(spec).is_none() {
+                problem(format!("hotkeys.{}", field), &format!("unknown hotkey {:?}", spec));
             }
         }
         
-        Ok(())
+        let appearance = &self.appearance;
+        if !Self::THEME_VARIANTS.contains(&appearance.theme_variant.as_str()) {
+            problem(
+                "appearance.theme_variant".to_string(),
+                &format!("unknown theme {:?}, must be one of {:?}", appearance.theme_variant, Self::THEME_VARIANTS),
+            );
+        }
+        if !(0.0..=1.0).contains(&appearance.transparency) {
+            problem("appearance.transparency".to_string(), "must be between 0.0 and 1.0");
+        }
+        
+        problems
+    }
+    
+    /// Valid settings that the launcher can't act on yet, so they are reported
+    /// instead of being ignored without a word
+    pub fn unsupported_settings(&self) -> Vec<ConfigProblem> {
+        let mut unsupported = Vec::new();
+        
+        if !self.hotkeys.toggle_launcher.is_empty() {
+            unsupported.push(ConfigProblem {
+                path: "hotkeys.toggle_launcher".to_string(),
+                message: "global hotkeys are not supported yet; bind `fade-launcher --toggle` in your desktop's shortcut settings instead".to_string(),
+            });
+        }
+        if self.appearance.blur_background {
+            unsupported.push(ConfigProblem {
+                path: "appearance.blur_background".to_string(),
+                message: "background blur is not supported yet".to_string(),
+            });
+        }
+        
+        unsupported
+    }
+    
+    /// The config written in `format`. With the current file as `existing`, a
+    /// TOML file keeps its comments and layout.
+    pub fn to_string_as(&self, format: ConfigFormat, existing: Option<&str>) -> Result<String, String> {
+        format.write(&self.to_json()?, existing)
+    }
+    
+    /// Get the configuration file path: the first of `config.toml`, `config.yaml`
+    /// and `config.json` that exists, or `config.json` if there is none yet
+    pub fn get_config_path() -> Option<PathBuf> {
+        let candidates = Self::config_file_candidates();
+        let existing = candidates.iter().find(|path| path.exists());
+        existing.or(candidates.last()).cloned()
+    }
+    
+    /// Every config file name, in order of precedence
+    pub fn config_file_candidates() -> Vec<PathBuf> {
+        let Some(dir) = Self::get_config_dir() else {
+            return Vec::new();
+        };
+        
+        ConfigFormat::PRECEDENCE
+            .iter()
+            .map(|format| dir.join(format.file_name()))
+            .collect()
+    }
+    
+    /// Get the directory holding the configuration and other persisted state
+    pub fn get_config_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("fade-launcher"))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A file as version 1 saved it: every section and field, no schema_version
+    fn v1_file(extensions: &[&str]) -> Value {
+        json!({
+            "ui": { "window_width": 800.0, "window_height": 600.0, "always_on_top": true, "hide_on_launch": true, "animation_speed": 1.0 },
+            "search": {
+                "max_results": 10,
+                "scan_paths": [],
+                "file_extensions": extensions,
+                "exclude_patterns": ["unins", "setup"],
+                "enable_fuzzy_search": true,
+            },
+            "hotkeys": { "toggle_launcher": "Alt+Space", "clear_search": "Escape", "launch_first": "Enter" },
+            "appearance": { "theme_variant": "fade", "transparency": 0.95, "blur_background": true, "show_particles": false, "gradient_animation": true },
+        })
+    }
+
+    #[test]
+    fn migrates_old_default_extensions_in_v1_files() {
+        let mut value = v1_file(&[".exe", ".lnk", ".bat", ".cmd"]);
+        assert!(Config::migrate(&mut value, 1));
+        assert_eq!(value["search"]["file_extensions"], json!([]));
+        assert_eq!(value["schema_version"], json!(Config::SCHEMA_VERSION));
+
+        // A list the user changed isn't the old default
+        let mut value = v1_file(&[".exe", ".lnk"]);
+        assert!(!Config::migrate(&mut value, 1));
+        assert_eq!(value["search"]["file_extensions"], json!([".exe", ".lnk"]));
+    }
+
+    #[test]
+    fn keeps_extensions_in_partial_files() {
+        // Version 1 couldn't read partial files, so this one was written by hand
+        let content = r#"{ "search": { "file_extensions": [".exe", ".lnk", ".bat", ".cmd"] } }"#;
+        let mut value: Value = serde_json::from_str(content).unwrap();
+        assert!(!Config::migrate(&mut value, 1));
+
+        let config = Config::parse(content, ConfigFormat::Json).unwrap();
+        assert_eq!(config.search.file_extensions, [".exe", ".lnk", ".bat", ".cmd"]);
+        assert_eq!(config.schema_version, Config::SCHEMA_VERSION);
+        assert_eq!(config.ui, UiConfig::default());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn writes_migrated_files_back_once() {
+        let dir = std::env::temp_dir().join(format!("fade-launcher-migrate-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("v1.json");
+        fs::write(&path, v1_file(&[".exe", ".lnk", ".bat", ".cmd"]).to_string()).unwrap();
+        Config::upgrade_file(&path).unwrap();
+        let upgraded: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(upgraded["schema_version"], json!(Config::SCHEMA_VERSION));
+        assert_eq!(upgraded["search"]["file_extensions"], json!([]));
+
+        let path = dir.join("partial.toml");
+        let content = "# Mine\n[ui]\nwindow_width = 1000\n";
+        fs::write(&path, content).unwrap();
+        Config::upgrade_file(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_newer_or_invalid_schema_versions() {
+        let newer = format!(r#"{{ "schema_version": {} }}"#, Config::SCHEMA_VERSION + 1);
+        assert!(Config::parse(&newer, ConfigFormat::Json).unwrap_err().contains("newer"));
+        assert!(Config::parse(r#"{ "schema_version": 0 }"#, ConfigFormat::Json).unwrap_err().contains("invalid"));
+        assert!(Config::parse("schema_version = \"2\"", ConfigFormat::Toml).unwrap_err().contains("invalid"));
+    }
+
+    /// The paths `validate` complains about, in order
+    fn problem_paths(config: &Config) -> Vec<String> {
+        config.validate().into_iter().map(|problem| problem.path).collect()
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(Config::default().validate().is_empty());
+        assert!(Config::default().ensure_valid().is_ok());
+    }
+
+    #[test]
+    fn validate_names_each_bad_field() {
+        let mut config = Config {
+            schema_version: 1,
+            ..Config::default()
+        };
+        config.ui.window_width = 0.0;
+        config.ui.window_height = f32::NAN;
+        config.ui.animation_speed = -1.0;
+        config.search.max_results = 0;
+        config.search.file_extensions = vec![".exe".to_string(), "lnk".to_string()];
+        config.appearance.transparency = 1.5;
+        assert_eq!(
+            problem_paths(&config),
+            [
+                "schema_version",
+                "ui.window_width",
+                "ui.window_height",
+                "ui.animation_speed",
+                "search.max_results",
+                "search.file_extensions[1]",
+                "appearance.transparency",
+            ]
+        );
+
+        let problems = config.validate();
+        assert_eq!(problems[1].to_string(), "ui.window_width: must be a positive number of pixels");
+        assert_eq!(problems[5].message, "must start with a dot, e.g. \".exe\"");
+    }
+
+    #[test]
+    fn validate_checks_scan_roots_and_rules() {
+        let mut config = Config::default();
+        let mut root = ScanRoot::new(PathBuf::new());
+        root.max_depth = 0;
+        root.priority_boost = f32::INFINITY;
+        root.extensions = Some(vec![".exe".to_string(), "bat".to_string()]);
+        config.search.scan_paths = vec![ScanRoot::new(PathBuf::from("/opt")), root];
+        config.search.include_patterns = vec![FilterRule::substring("")];
+        config.search.exclude_patterns = vec![
+            FilterRule::substring("setup"),
+            FilterRule { pattern: "(unclosed".to_string(), kind: RuleKind::Regex, target: RuleTarget::Name },
+        ];
+        assert_eq!(
+            problem_paths(&config),
+            [
+                "search.scan_paths[1].path",
+                "search.scan_paths[1].max_depth",
+                "search.scan_paths[1].priority_boost",
+                "search.scan_paths[1].extensions[1]",
+                "search.include_patterns[0].pattern",
+                "search.exclude_patterns[1].pattern",
+            ]
+        );
+        assert!(config.validate()[5].message.starts_with("invalid pattern: "));
+    }
+
+    #[test]
+    fn validate_rejects_unknown_hotkeys() {
+        let mut config = Config::default();
+        config.hotkeys.clear_search = "Ctrl+Esc".to_string();
+        config.hotkeys.launch_first = "Ctrl+Enter+K".to_string();
+        config.hotkeys.toggle_launcher = "Hyper+Space".to_string();
+        assert_eq!(problem_paths(&config), ["hotkeys.toggle_launcher", "hotkeys.launch_first"]);
+        assert_eq!(config.validate()[1].message, "unknown hotkey \"Ctrl+Enter+K\"");
+    }
+
+    #[test]
+    fn invalid_configs_are_not_saved() {
+        let mut config = Config::default();
+        config.search.max_results = 0;
+        config.appearance.transparency = -0.5;
+        assert_eq!(
+            config.ensure_valid().unwrap_err(),
+            "invalid settings: search.max_results: must be at least 1; appearance.transparency: must be between 0.0 and 1.0"
+        );
+        
+        let dir = std::env::temp_dir().join(format!("fade-launcher-save-test-{}", std::process::id()));
+        let path = dir.join("config.toml");
+        assert!(config.save_to(&path).is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn rejects_unknown_themes() {
+        let mut config = Config::default();
+        config.appearance.theme_variant = "neon".to_string();
+        assert_eq!(
+            config.validate()[0].to_string(),
+            "appearance.theme_variant: unknown theme \"neon\", must be one of [\"fade\"]"
+        );
+    }
+
+    #[test]
+    fn reports_settings_without_effect() {
+        let mut config = Config::default();
+        assert!(config.unsupported_settings().is_empty());
+
+        config.hotkeys.toggle_launcher = "Ctrl+Space".to_string();
+        config.appearance.blur_background = true;
+        let paths: Vec<String> = config.unsupported_settings().into_iter().map(|setting| setting.path).collect();
+        assert_eq!(paths, ["hotkeys.toggle_launcher", "appearance.blur_background"]);
+        // They are still valid, so saving isn't blocked
+        assert!(config.validate().is_empty());
+    }
+
+    /// The paths of the settings `parse_with_unknown` reports as unknown
+    fn unknown_paths(content: &str, format: ConfigFormat) -> Vec<String> {
+        let (_, unknown) = Config::parse_with_unknown(content, format).unwrap();
+        unknown.into_iter().map(|problem| problem.path).collect()
+    }
+
+    #[test]
+    fn reports_unknown_settings() {
+        let json = r#"{
+            "schema_version": 2,
+            "search": {
+                "max_result": 5,
+                "scan_paths": ["/usr/bin", { "path": "/opt", "max_dept": 2, "extensions": null }],
+                "exclude_patterns": [{ "pattern": "x", "kind": "glob", "targt": "path" }]
+            },
+            "apperance": { "transparency": 0.5 }
+        }"#;
+        assert_eq!(
+            unknown_paths(json, ConfigFormat::Json),
+            [
+                "apperance",
+                "search.exclude_patterns[0].targt",
+                "search.max_result",
+                "search.scan_paths[1].max_dept"
+            ]
+        );
+
+        let toml = "schema_version = 2\n[ui]\nwindow_widht = 900\n";
+        assert_eq!(unknown_paths(toml, ConfigFormat::Toml), ["ui.window_widht"]);
+        let yaml = "hotkeys:\n  launch_frist: Enter\n";
+        assert_eq!(unknown_paths(yaml, ConfigFormat::Yaml), ["hotkeys.launch_frist"]);
+
+        // Known settings, in full or shorthand, aren't reported
+        assert!(unknown_paths(&Config::default().to_string_as(ConfigFormat::Toml, None).unwrap(), ConfigFormat::Toml).is_empty());
+        assert!(unknown_paths(&v1_file(&[".exe"]).to_string(), ConfigFormat::Json).is_empty());
+    }
+
+    #[test]
+    fn loading_rejects_invalid_settings() {
+        let dir = std::env::temp_dir().join(format!("fade-launcher-load-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        fs::write(&path, "schema_version = 2\n[search]\nmax_results = 0\n").unwrap();
+
+        let error = Config::load_installed_file(&path, true).unwrap_err().to_string();
+        assert!(error.ends_with("invalid settings: search.max_results: must be at least 1"), "{}", error);
+        let config = Config::load_installed_file(&path, false).unwrap();
+        assert_eq!(config.search.max_results, 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,450 @@
+use serde_json::{Map, Number, Value};
+use std::{fmt, path::Path};
+use toml_edit::{ArrayOfTables, Document, InlineTable, Item, Table};
+
+/// File formats the config can be written in
+/// All of them are read into the JSON data model, so the config is parsed,
+/// migrated and validated the same way whatever the format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Lookup order when several config files exist; the first one found is used
+    pub const PRECEDENCE: [ConfigFormat; 3] = [ConfigFormat::Toml, ConfigFormat::Yaml, ConfigFormat::Json];
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ConfigFormat::Toml => "config.toml",
+            ConfigFormat::Yaml => "config.yaml",
+            ConfigFormat::Json => "config.json",
+        }
+    }
+
+    /// Format of a file by its extension, e.g. `.yml`
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+        Self::from_name(&extension)
+    }
+
+    /// Format by name, as given to `config convert`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            "json" => Some(ConfigFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Parse a file in this format; syntax errors name the line and column
+    pub fn parse(self, content: &str) -> Result<Value, String> {
+        match self {
+            ConfigFormat::Toml => {
+                let document: Document = content.parse().map_err(|e: toml_edit::TomlError| e.to_string().trim().to_string())?;
+                Ok(Toml::table_to_json(document.as_table()))
+            }
+            ConfigFormat::Yaml => Yaml::parse(content),
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Write `value` in this format. For TOML, `existing` is the current file:
+    /// only changed values are replaced, so its comments and layout survive.
+    pub fn write(self, value: &Value, existing: Option<&str>) -> Result<String, String> {
+        match self {
+            ConfigFormat::Toml => {
+                let Value::Object(object) = value else {
+                    return Err("a TOML config must be a table".to_string());
+                };
+                let mut document = existing
+                    .and_then(|content| content.parse::<Document>().ok())
+                    .unwrap_or_default();
+                Toml::merge_table(document.as_table_mut(), object);
+                Ok(document.to_string())
+            }
+            ConfigFormat::Yaml => Yaml::write(value),
+            ConfigFormat::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Yaml => "YAML",
+            ConfigFormat::Json => "JSON",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Conversion between TOML documents and JSON values
+struct Toml;
+
+impl Toml {
+    fn table_to_json(table: &Table) -> Value {
+        let object = table
+            .iter()
+            .filter_map(|(key, item)| Some((key.to_string(), Self::item_to_json(item)?)))
+            .collect();
+        Value::Object(object)
+    }
+
+    fn item_to_json(item: &Item) -> Option<Value> {
+        match item {
+            Item::None => None,
+            Item::Value(value) => Some(Self::value_to_json(value)),
+            Item::Table(table) => Some(Self::table_to_json(table)),
+            Item::ArrayOfTables(tables) => Some(Value::Array(tables.iter().map(Self::table_to_json).collect())),
+        }
+    }
+
+    fn value_to_json(value: &toml_edit::Value) -> Value {
+        match value {
+            toml_edit::Value::String(s) => Value::String(s.value().clone()),
+            toml_edit::Value::Integer(i) => Value::from(*i.value()),
+            toml_edit::Value::Float(f) => Number::from_f64(*f.value()).map_or(Value::Null, Value::Number),
+            toml_edit::Value::Boolean(b) => Value::Bool(*b.value()),
+            toml_edit::Value::Datetime(d) => Value::String(d.value().to_string()),
+            toml_edit::Value::Array(array) => Value::Array(array.iter().map(Self::value_to_json).collect()),
+            toml_edit::Value::InlineTable(table) => Value::Object(
+                table
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), Self::value_to_json(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// TOML has no null, so nulls (unset optional fields) are left out
+    fn json_to_item(value: &Value) -> Option<Item> {
+        match value {
+            Value::Object(object) => Some(Item::Table(Self::json_to_table(object))),
+            Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object) => {
+                let mut tables = ArrayOfTables::new();
+                for item in items.iter().filter_map(Value::as_object) {
+                    tables.push(Self::json_to_table(item));
+                }
+                Some(Item::ArrayOfTables(tables))
+            }
+            _ => Self::json_to_value(value).map(Item::Value),
+        }
+    }
+
+    fn json_to_table(object: &Map<String, Value>) -> Table {
+        let mut table = Table::new();
+        for (key, value) in object {
+            if let Some(item) = Self::json_to_item(value) {
+                table.insert(key, item);
+            }
+        }
+        table
+    }
+
+    fn json_to_value(value: &Value) -> Option<toml_edit::Value> {
+        match value {
+            Value::Null => None,
+            Value::Bool(b) => Some((*b).into()),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => Some(i.into()),
+                None => n.as_f64().map(Into::into),
+            },
+            Value::String(s) => Some(s.as_str().into()),
+            Value::Array(items) => Some(toml_edit::Value::Array(items.iter().filter_map(Self::json_to_value).collect())),
+            Value::Object(object) => {
+                let mut table = InlineTable::new();
+                for (key, value) in object {
+                    if let Some(value) = Self::json_to_value(value) {
+                        table.insert(key, value);
+                    }
+                }
+                Some(toml_edit::Value::InlineTable(table))
+            }
+        }
+    }
+
+    /// Bring `table` in line with `object`, leaving unchanged entries as they are
+    fn merge_table(table: &mut Table, object: &Map<String, Value>) {
+        let stale: Vec<String> = table
+            .iter()
+            .map(|(key, _)| key.to_string())
+            .filter(|key| object.get(key).is_none_or(Value::is_null))
+            .collect();
+        for key in stale {
+            table.remove(&key);
+        }
+
+        for (key, value) in object {
+            let Some(existing) = table.get_mut(key) else {
+                if let Some(item) = Self::json_to_item(value) {
+                    table.insert(key, item);
+                }
+                continue;
+            };
+
+            match (existing, value) {
+                (Item::Table(table), Value::Object(object)) => Self::merge_table(table, object),
+                (Item::ArrayOfTables(tables), Value::Array(items))
+                    if !items.is_empty() && items.iter().all(Value::is_object) =>
+                {
+                    while tables.len() > items.len() {
+                        tables.remove(tables.len() - 1);
+                    }
+                    for (i, item) in items.iter().filter_map(Value::as_object).enumerate() {
+                        match tables.get_mut(i) {
+                            Some(table) => Self::merge_table(table, item),
+                            None => tables.push(Self::json_to_table(item)),
+                        }
+                    }
+                }
+                (Item::Value(existing), _) => {
+                    if !Self::same_value(&Self::value_to_json(existing), value) {
+                        if let Some(mut replacement) = Self::json_to_value(value) {
+                            // Keep the comments around the old value
+                            *replacement.decor_mut() = existing.decor().clone();
+                            *existing = replacement;
+                        }
+                    }
+                }
+                (existing, _) => {
+                    if let Some(item) = Self::json_to_item(value) {
+                        *existing = item;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Equal, counting `800` and `800.0` as the same number
+    fn same_value(a: &Value, b: &Value) -> bool {
+        match (a, b) {
+            (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+            _ => a == b,
+        }
+    }
+}
+
+/// YAML files, read and written by `serde_yaml`
+struct Yaml;
+
+impl Yaml {
+    fn parse(content: &str) -> Result<Value, String> {
+        // Through serde_yaml's own value, which rejects duplicate keys
+        let yaml: serde_yaml::Value = serde_yaml::from_str(content).map_err(|e| Self::error_message(&e))?;
+        match serde_json::to_value(yaml).map_err(|e| e.to_string())? {
+            // An empty file or one with only comments
+            Value::Null => Ok(Value::Object(Map::new())),
+            value => Ok(value),
+        }
+    }
+
+    fn write(value: &Value) -> Result<String, String> {
+        serde_yaml::to_string(&Self::without_nulls(value)).map_err(|e| e.to_string())
+    }
+
+    /// The error with its position first, like the TOML and JSON ones
+    fn error_message(error: &serde_yaml::Error) -> String {
+        let message = error.to_string();
+        match error.location() {
+            Some(location) => {
+                let message = message.split(" at line ").next().unwrap_or(&message);
+                format!("line {}, column {}: {}", location.line(), location.column(), message)
+            }
+            None => message,
+        }
+    }
+
+    /// Nulls are left out of mappings, so a mapping of only nulls is written as `{}`
+    fn without_nulls(value: &Value) -> Value {
+        match value {
+            Value::Object(object) => Value::Object(
+                object
+                    .iter()
+                    .filter(|(_, value)| !value.is_null())
+                    .map(|(key, value)| (key.clone(), Self::without_nulls(value)))
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(items.iter().map(Self::without_nulls).collect()),
+            _ => value.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{Config, ScanRoot},
+        filter_rules::{FilterRule, RuleKind, RuleTarget},
+    };
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    /// Every field changed from its default, with strings YAML would misread
+    /// if they weren't quoted
+    fn every_field() -> Config {
+        let mut config = Config::default();
+
+        config.ui.window_width = 1024.5;
+        config.ui.window_height = 480.0;
+        config.ui.always_on_top = false;
+        config.ui.hide_on_launch = false;
+        config.ui.animation_speed = 0.0;
+
+        config.search.max_results = 25;
+        config.search.scan_paths = vec![
+            ScanRoot::new(PathBuf::from("/usr/bin")),
+            ScanRoot {
+                path: PathBuf::from(r#"C:\Program Files\"Games" # 1"#),
+                max_depth: 3,
+                follow_links: true,
+                extensions: Some(vec![".exe".to_string(), ".lnk".to_string()]),
+                include_hidden: true,
+                priority_boost: -0.5,
+            },
+            ScanRoot {
+                extensions: Some(Vec::new()),
+                ..ScanRoot::new(PathBuf::from("~/it's: here"))
+            },
+        ];
+        config.search.file_extensions = vec![".desktop".to_string(), ".AppImage".to_string()];
+        config.search.include_patterns = vec![FilterRule::substring("launcher")];
+        config.search.exclude_patterns = vec![
+            FilterRule::substring("null"),
+            FilterRule {
+                pattern: "**/*crash*".to_string(),
+                kind: RuleKind::Glob,
+                target: RuleTarget::Path,
+            },
+            FilterRule {
+                pattern: "^(true|- x)$ # not a comment".to_string(),
+                kind: RuleKind::Regex,
+                target: RuleTarget::Name,
+            },
+        ];
+        config.search.enable_fuzzy_search = false;
+
+        config.hotkeys.toggle_launcher = "Ctrl+Alt+Space".to_string();
+        config.hotkeys.clear_search = "Escape".to_string();
+        config.hotkeys.launch_first = "Shift+Enter".to_string();
+
+        config.appearance.theme_variant = "neon: dark".to_string();
+        config.appearance.transparency = 0.5;
+        config.appearance.blur_background = true;
+        config.appearance.show_particles = true;
+        config.appearance.gradient_animation = false;
+
+        config
+    }
+
+    #[test]
+    fn yaml_round_trips_every_config_field() {
+        let config = every_field();
+        let value = config.to_json().unwrap();
+
+        let text = ConfigFormat::Yaml.write(&value, None).unwrap();
+        assert_eq!(ConfigFormat::Yaml.parse(&text).unwrap(), value, "{}", text);
+        assert_eq!(Config::parse(&text, ConfigFormat::Yaml).unwrap(), config);
+
+        let defaults = Config::default();
+        let text = ConfigFormat::Yaml.write(&defaults.to_json().unwrap(), None).unwrap();
+        assert_eq!(Config::parse(&text, ConfigFormat::Yaml).unwrap(), defaults);
+    }
+
+    #[test]
+    fn yaml_parses_hand_written_files() {
+        let text = r#"
+# Written by hand
+---
+ui:
+  window_width: 900   # pixels
+  always_on_top: no-quotes # a plain string
+search:
+  scan_paths:
+  - /usr/bin
+  - path: "/opt/my apps"
+    max_depth: 2
+    extensions: [.sh, ".run"]
+  -
+    path: '/home/me/it''s'
+    extensions: ~
+  exclude_patterns:
+    - {pattern: "a, b", kind: glob}
+    - "tab\there"
+  file_extensions: []
+hotkeys:
+  clear_search: null
+  launch_first:
+appearance: {}
+"#;
+
+        let expected = json!({
+            "ui": { "window_width": 900, "always_on_top": "no-quotes" },
+            "search": {
+                "scan_paths": [
+                    "/usr/bin",
+                    { "path": "/opt/my apps", "max_depth": 2, "extensions": [".sh", ".run"] },
+                    { "path": "/home/me/it's", "extensions": null },
+                ],
+                "exclude_patterns": [{ "pattern": "a, b", "kind": "glob" }, "tab\there"],
+                "file_extensions": [],
+            },
+            "hotkeys": { "clear_search": null, "launch_first": null },
+            "appearance": {},
+        });
+        assert_eq!(ConfigFormat::Yaml.parse(text).unwrap(), expected);
+        assert_eq!(ConfigFormat::Yaml.parse("# only a comment\n").unwrap(), json!({}));
+    }
+
+    #[test]
+    fn yaml_writes_mappings_of_nulls_as_empty() {
+        let value = json!({
+            "a": { "b": null },
+            "c": [{ "d": null }, { "e": 1, "f": null }, null],
+            "g": null,
+        });
+
+        let text = ConfigFormat::Yaml.write(&value, None).unwrap();
+        let expected = json!({ "a": {}, "c": [{}, { "e": 1 }, null] });
+        assert_eq!(ConfigFormat::Yaml.parse(&text).unwrap(), expected, "{}", text);
+
+        let text = ConfigFormat::Yaml.write(&json!([{ "a": null }]), None).unwrap();
+        assert_eq!(ConfigFormat::Yaml.parse(&text).unwrap(), json!([{}]));
+    }
+
+    #[test]
+    fn yaml_reports_errors_with_line() {
+        let error = |text: &str| ConfigFormat::Yaml.parse(text).unwrap_err();
+
+        assert!(error("ui:\n\twindow_width: 1\n").starts_with("line 2"));
+        assert!(error("a: 1\nb: 2\na: 3\n").contains("duplicate entry with key \"a\""));
+        assert!(error("a: [1, 2\n").starts_with("line 2"));
+        assert!(error("a:\n    b: 1\n  c: 2\n").starts_with("line 3"));
+    }
+
+    #[test]
+    fn yaml_keeps_apostrophes_in_plain_values() {
+        let text = "name: Rider's Tool  # note\npath: C:\\Users\\it's me\\app.exe\npattern: don't # skip\n";
+        let expected = json!({
+            "name": "Rider's Tool",
+            "path": "C:\\Users\\it's me\\app.exe",
+            "pattern": "don't",
+        });
+        assert_eq!(ConfigFormat::Yaml.parse(text).unwrap(), expected);
+    }
+
+    #[test]
+    fn toml_write_replaces_only_changed_values() {
+        let existing = "# My launcher\n[ui]\nwindow_width = 800 # wide enough\nwindow_height = 600\n\n[search]\nscan_paths = [\"/usr/bin\"]\n";
+        let mut value = ConfigFormat::Toml.parse(existing).unwrap();
+        value["ui"]["window_height"] = json!(700);
+
+        let written = ConfigFormat::Toml.write(&value, Some(existing)).unwrap();
+        assert_eq!(written, existing.replace("window_height = 600", "window_height = 700"));
+    }
+}
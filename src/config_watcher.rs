@@ -0,0 +1,63 @@
+use crate::config::Config;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    fs,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver},
+};
+
+/// Watches the config file so edits apply without a restart
+/// The config directory is watched rather than the file, since editors often
+/// save by replacing the file.
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher, // Stops watching when dropped
+    events: Receiver<notify::Result<Event>>,
+    config_paths: Vec<PathBuf>, // Every config file name, since adding a TOML file overrides the JSON one
+}
+
+impl ConfigWatcher {
+    pub fn start() -> Option<Self> {
+        let config_dir = Config::get_config_dir()?;
+        if let Err(e) = fs::create_dir_all(&config_dir) {
+            eprintln!("Failed to create config directory {:?}: {}", config_dir, e);
+            return None;
+        }
+
+        let (tx, events) = mpsc::channel();
+        let mut watcher = match notify::recommended_watcher(tx) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                eprintln!("Failed to start config watcher: {}", e);
+                return None;
+            }
+        };
+
+        if let Err(e) = watcher.watch(&config_dir, RecursiveMode::NonRecursive) {
+            eprintln!("Failed to watch {:?}: {}", config_dir, e);
+            return None;
+        }
+
+        Some(Self {
+            _watcher: watcher,
+            events,
+            config_paths: Config::config_file_candidates(),
+        })
+    }
+
+    /// Whether the config file changed since the last call
+    pub fn poll(&self) -> bool {
+        let mut changed = false;
+
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) => {
+                    changed |= !matches!(event.kind, EventKind::Access(_))
+                        && event.paths.iter().any(|path| self.config_paths.contains(path));
+                }
+                Err(e) => eprintln!("Config watcher error: {}", e),
+            }
+        }
+
+        changed
+    }
+}
@@ -0,0 +1,604 @@
+use crate::{app_info::AppInfo, platform};
+use std::{
+    collections::HashMap,
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A parsed freedesktop.org Desktop Entry (`.desktop` file) of type Application
+/// Localized keys are resolved against the current locale when parsing.
+#[derive(Debug, Clone, Default)]
+pub struct DesktopEntry {
+    pub name: String,
+    pub generic_name: Option<String>,
+    pub comment: Option<String>,
+    pub keywords: Vec<String>,
+    pub icon: Option<String>,
+    pub exec: Option<String>,
+    pub try_exec: Option<String>,
+    pub working_dir: Option<String>,
+    pub no_display: bool,
+    pub hidden: bool,
+    pub only_show_in: Vec<String>,
+    pub not_show_in: Vec<String>,
+}
+
+impl DesktopEntry {
+    const GROUP: &'static str = "Desktop Entry";
+
+    /// Read and parse the desktop entry at `path` using the current locale
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        Self::parse(&content, &Self::current_locales())
+    }
+
+    /// Parse desktop entry content. `locales` lists the locale suffixes to try for
+    /// localized keys, most specific first (see [`Self::current_locales`]).
+    /// Returns `None` for malformed entries and entries that aren't applications.
+    pub fn parse(content: &str, locales: &[String]) -> Option<Self> {
+        // key -> (default value, localized values by locale suffix)
+        let mut values: HashMap<&str, (Option<&str>, HashMap<&str, &str>)> = HashMap::new();
+        let mut in_entry_group = false;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                in_entry_group = &line[1..line.len() - 1] == Self::GROUP;
+                continue;
+            }
+
+            if !in_entry_group {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            match key.split_once('[') {
+                Some((base, locale)) if locale.ends_with(']') => {
+                    let locale = &locale[..locale.len() - 1];
+                    values.entry(base).or_default().1.insert(locale, value);
+                }
+                _ => {
+                    // The first occurrence of a key wins
+                    let entry = values.entry(key).or_default();
+                    entry.0.get_or_insert(value);
+                }
+            }
+        }
+
+        let lookup = |key: &str| -> Option<&str> {
+            let (default, localized) = values.get(key)?;
+            locales
+                .iter()
+                .find_map(|locale| localized.get(locale.as_str()).copied())
+                .or(*default)
+        };
+        let string = |key: &str| lookup(key).map(Self::unescape).filter(|v| !v.is_empty());
+        let list = |key: &str| lookup(key).map(Self::split_list).unwrap_or_default();
+        let boolean = |key: &str| lookup(key) == Some("true");
+
+        // Type is required; entries without one are malformed
+        if lookup("Type")? != "Application" {
+            return None;
+        }
+
+        Some(Self {
+            name: string("Name")?,
+            generic_name: string("GenericName"),
+            comment: string("Comment"),
+            keywords: list("Keywords"),
+            icon: string("Icon"),
+            exec: string("Exec"),
+            try_exec: string("TryExec"),
+            working_dir: string("Path"),
+            no_display: boolean("NoDisplay"),
+            hidden: boolean("Hidden"),
+            only_show_in: list("OnlyShowIn"),
+            not_show_in: list("NotShowIn"),
+        })
+    }
+
+    /// Whether the entry should be listed in the current desktop environment:
+    /// not Hidden or NoDisplay, allowed by OnlyShowIn/NotShowIn, and its TryExec
+    /// binary (if any) is installed
+    pub fn is_visible(&self) -> bool {
+        self.is_visible_in(&Self::current_desktops())
+    }
+
+    /// [`Self::is_visible`] in the desktop environments named by `desktops`
+    fn is_visible_in(&self, desktops: &[String]) -> bool {
+        if self.hidden || self.no_display {
+            return false;
+        }
+
+        if !self.only_show_in.is_empty() && !desktops.iter().any(|d| self.only_show_in.contains(d)) {
+            return false;
+        }
+
+        if desktops.iter().any(|d| self.not_show_in.contains(d)) {
+            return false;
+        }
+
+        match &self.try_exec {
+            Some(try_exec) => Self::find_executable(try_exec).is_some(),
+            None => true,
+        }
+    }
+
+    /// Convert to an index entry; the `.desktop` file path is kept as the app path
+    pub fn to_app_info(&self, path: &Path) -> AppInfo {
+        let mut keywords = self.keywords.clone();
+        if let Some(generic_name) = &self.generic_name {
+            keywords.insert(0, generic_name.clone());
+        }
+
+        AppInfo {
+            name: self.name.clone(),
+            path: path.to_string_lossy().to_string(),
+            target: self.exec_target(),
+            sources: vec![path.to_string_lossy().to_string()],
+            path_hint: None,
+            icon_path: self.icon.clone(),
+            description: self.comment.clone().or_else(|| self.generic_name.clone()),
+            hotkey: None,
+            keywords,
+            version_info: None,
+            score: 0.0,
+            name_matches: Vec::new(),
+            path_matches: Vec::new(),
+        }
+    }
+
+    /// Resolved program of an Exec line that runs a single command without arguments,
+    /// so the entry can be merged with the same program found on `PATH`
+    fn exec_target(&self) -> Option<String> {
+        let args = self.command_line(Path::new(""))?;
+        let [program] = args.as_slice() else {
+            return None;
+        };
+
+        let program = Self::find_executable(program)?;
+        let resolved = platform::canonical_path(&program).unwrap_or(program);
+        Some(resolved.to_string_lossy().to_string())
+    }
+
+    /// Expand the Exec key into an argument vector, dropping file and URL field
+    /// codes since the launcher never passes files
+    pub fn command_line(&self, path: &Path) -> Option<Vec<String>> {
+        let exec = self.exec.as_deref()?;
+        let mut args = Vec::new();
+
+        for arg in Self::split_exec(exec) {
+            match arg.as_str() {
+                "%f" | "%F" | "%u" | "%U" => continue,
+                "%i" => {
+                    if let Some(icon) = &self.icon {
+                        args.push("--icon".to_string());
+                        args.push(icon.clone());
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            let mut expanded = String::new();
+            let mut chars = arg.chars();
+            while let Some(c) = chars.next() {
+                if c != '%' {
+                    expanded.push(c);
+                    continue;
+                }
+
+                match chars.next() {
+                    Some('%') => expanded.push('%'),
+                    Some('c') => expanded.push_str(&self.name),
+                    Some('k') => expanded.push_str(&path.to_string_lossy()),
+                    // %f, %u and the deprecated codes expand to nothing
+                    _ => {}
+                }
+            }
+
+            // Drop arguments that were only field codes, but keep a quoted `""`
+            if !expanded.is_empty() || arg.is_empty() {
+                args.push(expanded);
+            }
+        }
+
+        if args.is_empty() {
+            None
+        } else {
+            Some(args)
+        }
+    }
+
+    /// `applications` directories in precedence order: `$XDG_DATA_HOME` first,
+    /// then each entry of `$XDG_DATA_DIRS`
+    pub fn application_dirs() -> Vec<PathBuf> {
+        Self::application_dirs_from(
+            env::var_os("XDG_DATA_HOME"),
+            env::var_os("XDG_DATA_DIRS"),
+            dirs::home_dir(),
+        )
+    }
+
+    /// [`Self::application_dirs`] for the given variables and home directory
+    fn application_dirs_from(data_home: Option<OsString>, data_dirs: Option<OsString>, home: Option<PathBuf>) -> Vec<PathBuf> {
+        let data_home = data_home
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home.map(|home| home.join(".local").join("share")));
+
+        let data_dirs = data_dirs
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+
+        data_home
+            .into_iter()
+            .chain(env::split_paths(&data_dirs))
+            .map(|dir| dir.join("applications"))
+            .collect()
+    }
+
+    /// Desktop file ID of `path` below the applications directory `root`:
+    /// the relative path with `/` replaced by `-`, e.g. `kde4/konsole.desktop`
+    /// becomes `kde4-konsole.desktop`. Entries with the same ID in a directory
+    /// of lower precedence are overridden.
+    pub fn desktop_file_id(root: &Path, path: &Path) -> Option<String> {
+        if path.extension()? != "desktop" {
+            return None;
+        }
+
+        let relative = path.strip_prefix(root).ok()?;
+        let parts: Vec<_> = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+        Some(parts.join("-"))
+    }
+
+    /// Existing files below `root` whose desktop file ID is `id`, the reverse of
+    /// [`Self::desktop_file_id`]. There can be several, since `kde4-konsole.desktop`
+    /// and `kde4/konsole.desktop` share an ID.
+    pub fn desktop_file_paths(root: &Path, id: &str) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
+        let file = root.join(id);
+        if file.is_file() {
+            paths.push(file);
+        }
+        for (dash, _) in id.match_indices('-').filter(|(dash, _)| *dash > 0) {
+            let dir = root.join(&id[..dash]);
+            if dir.is_dir() {
+                paths.extend(Self::desktop_file_paths(&dir, &id[dash + 1..]));
+            }
+        }
+
+        paths
+    }
+
+    /// Locale suffixes to try for localized keys, from `LC_ALL`, `LC_MESSAGES` or
+    /// `LANG`: `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER`, `lang`
+    pub fn current_locales() -> Vec<String> {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        Self::locales_for(&locale)
+    }
+
+    /// Locale suffixes to try for the locale `locale`, such as `de_DE.UTF-8@euro`
+    fn locales_for(locale: &str) -> Vec<String> {
+        // Strip the encoding, keeping any modifier
+        let (locale, modifier) = match locale.split_once('@') {
+            Some((locale, modifier)) => (locale.to_string(), Some(modifier.to_string())),
+            None => (locale.to_string(), None),
+        };
+        let locale = locale.split('.').next().unwrap_or_default();
+        if locale.is_empty() || locale == "C" || locale == "POSIX" {
+            return Vec::new();
+        }
+
+        let (lang, country) = match locale.split_once('_') {
+            Some((lang, country)) => (lang, Some(country)),
+            None => (locale, None),
+        };
+
+        let mut locales = Vec::new();
+        if let (Some(country), Some(modifier)) = (country, &modifier) {
+            locales.push(format!("{}_{}@{}", lang, country, modifier));
+        }
+        if let Some(country) = country {
+            locales.push(format!("{}_{}", lang, country));
+        }
+        if let Some(modifier) = &modifier {
+            locales.push(format!("{}@{}", lang, modifier));
+        }
+        locales.push(lang.to_string());
+        locales
+    }
+
+    fn current_desktops() -> Vec<String> {
+        env::var("XDG_CURRENT_DESKTOP")
+            .map(|value| value.split(':').filter(|d| !d.is_empty()).map(String::from).collect())
+            .unwrap_or_default()
+    }
+
+    fn find_executable(program: &str) -> Option<PathBuf> {
+        let program_path = Path::new(program);
+        if program_path.is_absolute() {
+            return program_path.is_file().then(|| program_path.to_path_buf());
+        }
+
+        env::var_os("PATH").and_then(|path| {
+            env::split_paths(&path)
+                .map(|dir| dir.join(program))
+                .find(|candidate| candidate.is_file())
+        })
+    }
+
+    /// Resolve the `\s`, `\n`, `\t`, `\r` and `\\` escapes of string values
+    fn unescape(value: &str) -> String {
+        let mut result = String::with_capacity(value.len());
+        let mut chars = value.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                result.push(c);
+                continue;
+            }
+
+            match chars.next() {
+                Some('s') => result.push(' '),
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('r') => result.push('\r'),
+                Some(other) => {
+                    // Unknown escapes (such as `\;` in lists) are kept for later stages
+                    if other != '\\' {
+                        result.push('\\');
+                    }
+                    result.push(other);
+                }
+                None => result.push('\\'),
+            }
+        }
+
+        result
+    }
+
+    /// Split a `;`-separated list value, honouring `\;` escapes
+    fn split_list(value: &str) -> Vec<String> {
+        let mut items = Vec::new();
+        let mut current = String::new();
+        let mut chars = value.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(';') => current.push(';'),
+                    Some(other) => {
+                        current.push('\\');
+                        current.push(other);
+                    }
+                    None => current.push('\\'),
+                },
+                ';' => items.push(Self::unescape(&std::mem::take(&mut current))),
+                _ => current.push(c),
+            }
+        }
+        items.push(Self::unescape(&current));
+
+        items.retain(|item| !item.is_empty());
+        items
+    }
+
+    /// Split an Exec value into arguments following the spec's quoting rules:
+    /// double-quoted arguments may escape `"`, `` ` ``, `$` and `\` with a backslash
+    fn split_exec(exec: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut current = String::new();
+        let mut in_quotes = false;
+        let mut has_arg = false;
+        let mut chars = exec.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    in_quotes = !in_quotes;
+                    has_arg = true;
+                }
+                '\\' if in_quotes => {
+                    if let Some(next) = chars.next() {
+                        current.push(next);
+                    }
+                }
+                c if c.is_whitespace() && !in_quotes => {
+                    if has_arg {
+                        args.push(std::mem::take(&mut current));
+                        has_arg = false;
+                    }
+                }
+                _ => {
+                    current.push(c);
+                    has_arg = true;
+                }
+            }
+        }
+
+        if has_arg {
+            args.push(current);
+        }
+
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Option<DesktopEntry> {
+        DesktopEntry::parse(content, &[])
+    }
+
+    /// An application entry with `extra` lines added to its group
+    fn app(extra: &str) -> DesktopEntry {
+        parse(&format!("[Desktop Entry]\nType=Application\nName=App\n{}", extra)).unwrap()
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    /// A directory under the system temp dir that only this test uses
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("fade-launcher-desktop-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn parses_only_the_desktop_entry_group() {
+        let entry = parse(
+            "# Comment\n[Desktop Entry]\nType=Application\nName = Files \nName=Ignored\nComment=Browse\nKeywords=folder;explorer;\nNoDisplay=false\n\n[Desktop Action new]\nName=New Window\nExec=files --new\n",
+        )
+        .unwrap();
+
+        assert_eq!(entry.name, "Files");
+        assert_eq!(entry.comment.as_deref(), Some("Browse"));
+        assert_eq!(entry.keywords, strings(&["folder", "explorer"]));
+        assert_eq!(entry.exec, None);
+        assert!(!entry.no_display);
+    }
+
+    #[test]
+    fn requires_an_application_type_and_name() {
+        assert!(parse("[Desktop Entry]\nName=No type\nExec=app\n").is_none());
+        assert!(parse("[Desktop Entry]\nType=Link\nName=Site\nURL=https://example.com\n").is_none());
+        assert!(parse("[Desktop Entry]\nType=Application\nExec=app\n").is_none());
+        assert!(parse("[Desktop Entry]\nType=Application\nName=\n").is_none());
+        assert!(parse("[Other]\nType=Application\nName=App\n").is_none());
+    }
+
+    #[test]
+    fn prefers_the_most_specific_locale() {
+        let content = "[Desktop Entry]\nType=Application\nName=Files\nName[de]=Dateien\nName[de_AT]=Dateien (AT)\nName[sr@latin]=Datoteke\nComment=Browse\n";
+        let name = |locale: &str| DesktopEntry::parse(content, &DesktopEntry::locales_for(locale)).unwrap().name;
+
+        assert_eq!(name("de_AT.UTF-8"), "Dateien (AT)");
+        assert_eq!(name("de_CH.UTF-8"), "Dateien");
+        assert_eq!(name("sr_RS@latin"), "Datoteke");
+        assert_eq!(name("fr_FR"), "Files");
+        assert_eq!(name("C"), "Files");
+        // Unlocalized keys fall back to the default
+        let entry = DesktopEntry::parse(content, &DesktopEntry::locales_for("de_AT")).unwrap();
+        assert_eq!(entry.comment.as_deref(), Some("Browse"));
+    }
+
+    #[test]
+    fn lists_locale_suffixes_in_lookup_order() {
+        assert_eq!(DesktopEntry::locales_for("sr_RS.UTF-8@latin"), strings(&["sr_RS@latin", "sr_RS", "sr@latin", "sr"]));
+        assert_eq!(DesktopEntry::locales_for("de_DE.UTF-8"), strings(&["de_DE", "de"]));
+        assert_eq!(DesktopEntry::locales_for("en"), strings(&["en"]));
+        assert!(DesktopEntry::locales_for("POSIX").is_empty());
+        assert!(DesktopEntry::locales_for("").is_empty());
+    }
+
+    #[test]
+    fn resolves_escapes() {
+        let entry = app(r"Comment=Tab\there\sand\nline\\end
+Keywords=a\;b;c\\;
+");
+        assert_eq!(entry.comment.as_deref(), Some("Tab\there and\nline\\end"));
+        assert_eq!(entry.keywords, strings(&["a;b", "c\\"]));
+    }
+
+    #[test]
+    fn splits_exec_lines_and_expands_field_codes() {
+        let path = Path::new("/usr/share/applications/app.desktop");
+        let command = |exec: &str| app(&format!("Icon=app-icon\nExec={}", exec)).command_line(path).unwrap();
+
+        assert_eq!(command("app %U"), strings(&["app"]));
+        assert_eq!(command("app --file=%f %F %u"), strings(&["app", "--file="]));
+        assert_eq!(command("app 100%% %c"), strings(&["app", "100%", "App"]));
+        assert_eq!(command("app %k %i"), strings(&["app", "/usr/share/applications/app.desktop", "--icon", "app-icon"]));
+        assert_eq!(command("\"/opt/my app/run\" \"\" arg"), strings(&["/opt/my app/run", "", "arg"]));
+        // The file escapes `\\` to `\`, then the quoting escapes `\$` to `$`
+        assert_eq!(command(r#"sh -c "echo \\$HOME \\"hi\\"""#), strings(&["sh", "-c", "echo $HOME \"hi\""]));
+
+        assert!(app("").command_line(path).is_none());
+        assert!(app("Exec=%f").command_line(path).is_none());
+        assert_eq!(app("Exec=app %i").command_line(path).unwrap(), strings(&["app"]));
+    }
+
+    #[test]
+    fn honours_visibility_keys() {
+        let gnome = strings(&["ubuntu", "GNOME"]);
+
+        assert!(app("").is_visible_in(&gnome));
+        assert!(!app("Hidden=true").is_visible_in(&gnome));
+        assert!(!app("NoDisplay=true").is_visible_in(&gnome));
+
+        assert!(app("OnlyShowIn=GNOME;XFCE;").is_visible_in(&gnome));
+        assert!(!app("OnlyShowIn=KDE;").is_visible_in(&gnome));
+        assert!(!app("OnlyShowIn=KDE;").is_visible_in(&[]));
+        assert!(!app("NotShowIn=GNOME;").is_visible_in(&gnome));
+        assert!(app("NotShowIn=KDE;").is_visible_in(&gnome));
+
+        let dir = temp_dir("try-exec");
+        let program = dir.join("installed");
+        fs::write(&program, "").unwrap();
+        assert!(app(&format!("TryExec={}", program.display())).is_visible_in(&gnome));
+        assert!(!app(&format!("TryExec={}", dir.join("missing").display())).is_visible_in(&gnome));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn lists_application_dirs_in_precedence_order() {
+        let dirs = DesktopEntry::application_dirs_from(
+            Some("/home/me/.data".into()),
+            Some("/opt/share:/usr/share".into()),
+            Some(PathBuf::from("/home/me")),
+        );
+        let expected = ["/home/me/.data/applications", "/opt/share/applications", "/usr/share/applications"];
+        assert_eq!(dirs, expected.map(PathBuf::from));
+
+        // Unset or empty variables use the spec's defaults
+        let dirs = DesktopEntry::application_dirs_from(Some("".into()), None, Some(PathBuf::from("/home/me")));
+        let expected = ["/home/me/.local/share/applications", "/usr/local/share/applications", "/usr/share/applications"];
+        assert_eq!(dirs, expected.map(PathBuf::from));
+    }
+
+    #[test]
+    fn maps_desktop_file_ids_to_paths_in_each_dir() {
+        let user = temp_dir("ids-user");
+        let system = temp_dir("ids-system");
+        fs::create_dir_all(user.join("kde4")).unwrap();
+        fs::write(user.join("kde4").join("konsole.desktop"), "").unwrap();
+        fs::write(system.join("kde4-konsole.desktop"), "").unwrap();
+        fs::write(system.join("firefox.desktop"), "").unwrap();
+
+        let id = |root: &Path, relative: &str| DesktopEntry::desktop_file_id(root, &root.join(relative));
+        assert_eq!(id(&user, "kde4/konsole.desktop").as_deref(), Some("kde4-konsole.desktop"));
+        assert_eq!(id(&system, "kde4-konsole.desktop").as_deref(), Some("kde4-konsole.desktop"));
+        assert_eq!(id(&system, "firefox.desktop").as_deref(), Some("firefox.desktop"));
+        assert_eq!(id(&system, "readme.txt"), None);
+        assert_eq!(DesktopEntry::desktop_file_id(&user, &system.join("firefox.desktop")), None);
+
+        // The same ID in both dirs, laid out differently
+        assert_eq!(DesktopEntry::desktop_file_paths(&user, "kde4-konsole.desktop"), [user.join("kde4").join("konsole.desktop")]);
+        assert_eq!(DesktopEntry::desktop_file_paths(&system, "kde4-konsole.desktop"), [system.join("kde4-konsole.desktop")]);
+        assert!(DesktopEntry::desktop_file_paths(&user, "firefox.desktop").is_empty());
+
+        fs::remove_dir_all(&user).unwrap();
+        fs::remove_dir_all(&system).unwrap();
+    }
+}
@@ -0,0 +1,446 @@
+use crate::{
+    app_info::AppInfo,
+    config::Config,
+    fuzzy::FuzzyMatcher,
+    scanner::ScanProgress,
+    ui::LauncherUI,
+};
+use eframe::egui;
+use std::{
+    io::{self, BufRead, Write},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// `--dmenu` mode: pick from newline-separated items on stdin with the launcher's
+/// list, matching and keyboard navigation. The choice is printed to stdout;
+/// Escape exits with status 1 like dmenu.
+pub struct DmenuPicker {
+    items: Vec<String>,
+    options: DmenuOptions,
+    enable_fuzzy_search: bool,
+    query: String,
+    filtered_query: Option<String>, // Query `results` were computed for
+    results: Vec<AppInfo>,
+    marked: Vec<String>, // Items checked with Tab in multi-select mode
+    ui: LauncherUI,
+    launch_shortcut: Option<egui::KeyboardShortcut>,
+    clear_shortcut: Option<egui::KeyboardShortcut>,
+    animation_time: f32,
+    animation_speed: f32,
+    selection: Arc<Mutex<Option<Vec<String>>>>, // Set when the user picked something
+}
+
+/// Flags after `--dmenu`
+#[derive(Debug, Clone)]
+pub struct DmenuOptions {
+    pub prompt: String,
+    pub case_sensitive: bool,
+    pub multi_select: bool, // Tab checks items, Enter prints every checked one
+}
+
+impl Default for DmenuOptions {
+    fn default() -> Self {
+        Self {
+            prompt: "Choose an item...".to_string(),
+            case_sensitive: false,
+            multi_select: false,
+        }
+    }
+}
+
+impl DmenuOptions {
+    const USAGE: &'static str = "\
+Usage: fade-launcher --dmenu [OPTIONS] < items
+
+Options:
+  -p, --prompt TEXT     Text shown above the filter box
+  -i, --ignore-case     Match case-insensitively (the default)
+  -s, --case-sensitive  Only match letters of the same case
+  -m, --multi-select    Check items with Tab; Enter prints every checked item";
+
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-p" | "--prompt" => options.prompt = args.next().ok_or("--prompt needs a value")?.clone(),
+                "-i" | "--ignore-case" => options.case_sensitive = false,
+                "-s" | "--case-sensitive" => options.case_sensitive = true,
+                "-m" | "--multi-select" => options.multi_select = true,
+                _ => return Err(format!("Unknown option {:?}", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+impl DmenuPicker {
+    /// Read the items, show the picker and print the selection. Returns the exit
+    /// code: 0 if something was picked, 1 if cancelled, 2 for bad arguments.
+    pub fn run(args: &[String]) -> i32 {
+        let options = match DmenuOptions::parse(args) {
+            Ok(options) => options,
+            Err(e) => {
+                eprintln!("{}\n\n{}", e, DmenuOptions::USAGE);
+                return 2;
+            }
+        };
+
+        let items: Vec<String> = io::stdin()
+            .lock()
+            .lines()
+            .map_while(Result::ok)
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+
+        let config = Config::try_load().unwrap_or_else(|e| {
+            eprintln!("Failed to load config, using defaults: {}", e);
+            Config::default()
+        });
+
+        let selection = Arc::new(Mutex::new(None));
+        let picker = Self::new(&config, items, options, Arc::clone(&selection));
+
+        let result = eframe::run_native(
+            "Fade Launcher",
+            crate::window::native_options(&config.ui),
+            Box::new(|cc| {
+                crate::window::setup_custom_fonts(&cc.egui_ctx);
+                crate::window::setup_style(&cc.egui_ctx);
+
+                Box::new(picker)
+            }),
+        );
+        if let Err(e) = result {
+            eprintln!("Failed to open the picker: {}", e);
+            return 1;
+        }
+
+        let selection = selection.lock().ok().and_then(|mut selection| selection.take());
+        Self::print_selection(selection, &mut io::stdout().lock())
+    }
+
+    /// Print the picked items one per line. Returns the exit code: 0 if something
+    /// was picked, 1 if cancelled.
+    fn print_selection(selection: Option<Vec<String>>, out: &mut impl Write) -> i32 {
+        match selection {
+            Some(items) => {
+                for item in items {
+                    let _ = writeln!(out, "{}", item);
+                }
+                0
+            }
+            None => 1,
+        }
+    }
+
+    fn new(config: &Config, items: Vec<String>, options: DmenuOptions, selection: Arc<Mutex<Option<Vec<String>>>>) -> Self {
+        let launch_shortcut = LauncherUI::parse_shortcut(&config.hotkeys.launch_first);
+        let mut ui = LauncherUI::new(&config.appearance, launch_shortcut);
+        ui.set_prompt(&options.prompt);
+
+        Self {
+            items,
+            options,
+            enable_fuzzy_search: config.search.enable_fuzzy_search,
+            query: String::new(),
+            filtered_query: None,
+            results: Vec::new(),
+            marked: Vec::new(),
+            ui,
+            launch_shortcut,
+            clear_shortcut: LauncherUI::parse_shortcut(&config.hotkeys.clear_search),
+            animation_time: 0.0,
+            animation_speed: config.ui.animation_speed,
+            selection,
+        }
+    }
+
+    /// Items matching the query, best first; every item while the query is empty
+    fn filter(&self) -> Vec<AppInfo> {
+        let query = self.query.trim();
+        if query.is_empty() {
+            return self.items.iter().map(|item| Self::entry(item, 0.0, Vec::new())).collect();
+        }
+
+        let mut hits: Vec<AppInfo> = self
+            .items
+            .iter()
+            .filter_map(|item| {
+                // Case-sensitive mode compares exact characters from the start, so
+                // an item with a same-case match anywhere is found
+                let case_sensitive = self.options.case_sensitive;
+                let (score, positions) = if self.enable_fuzzy_search {
+                    let hit = if case_sensitive {
+                        FuzzyMatcher::fuzzy_match_case_sensitive(item, query)?
+                    } else {
+                        FuzzyMatcher::fuzzy_match(item, query)?
+                    };
+                    (hit.score, hit.positions)
+                } else {
+                    let range = if case_sensitive {
+                        FuzzyMatcher::substring_range_case_sensitive(item, query)?
+                    } else {
+                        FuzzyMatcher::substring_range(item, query)?
+                    };
+                    (1.0, range.collect())
+                };
+
+                Some(Self::entry(item, score, FuzzyMatcher::positions_to_ranges(&positions)))
+            })
+            .collect();
+
+        // Stable, so equally good items keep their input order
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits
+    }
+
+    /// Picker items go through the launcher's result list as apps named after them
+    fn entry(item: &str, score: f32, name_matches: Vec<std::ops::Range<usize>>) -> AppInfo {
+        AppInfo {
+            name: item.to_string(),
+            path: item.to_string(),
+            target: None,
+            sources: Vec::new(),
+            path_hint: None,
+            icon_path: None,
+            description: None,
+            hotkey: None,
+            keywords: Vec::new(),
+            version_info: None,
+            score,
+            name_matches,
+            path_matches: Vec::new(),
+        }
+    }
+
+    fn finish(&mut self, ctx: &egui::Context, selection: Option<Vec<String>>) {
+        if let Ok(mut slot) = self.selection.lock() {
+            *slot = selection;
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+    }
+}
+
+impl eframe::App for DmenuPicker {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.show(ctx);
+    }
+}
+
+impl DmenuPicker {
+    /// One frame of the picker: filter, handle the keys and draw the list
+    fn show(&mut self, ctx: &egui::Context) {
+        self.animation_time += ctx.input(|i| i.unstable_dt) * self.animation_speed;
+
+        if self.filtered_query.as_ref() != Some(&self.query) {
+            self.results = self.filter();
+            self.filtered_query = Some(self.query.clone());
+            self.ui.reset_selection();
+        }
+
+        let pressed = |shortcut: Option<egui::KeyboardShortcut>| {
+            ctx.input(|i| shortcut.is_some_and(|shortcut| i.modifiers.matches_logically(shortcut.modifiers) && i.key_pressed(shortcut.logical_key)))
+        };
+        let cancelled = pressed(self.clear_shortcut);
+        if cancelled {
+            self.finish(ctx, None);
+            return;
+        }
+
+        // Tab checks the highlighted item instead of moving focus
+        if self.options.multi_select && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+            if let Some(item) = self.results.get(self.ui.selected_index()) {
+                match self.marked.iter().position(|marked| *marked == item.path) {
+                    Some(index) => {
+                        self.marked.remove(index);
+                    }
+                    None => self.marked.push(item.path.clone()),
+                }
+                self.ui.set_marked(&self.marked);
+            }
+        }
+
+        // Nothing matches: Enter picks the typed text
+        let typed = self.query.trim().to_string();
+        if self.results.is_empty() && !typed.is_empty() && pressed(self.launch_shortcut) {
+            self.finish(ctx, Some(vec![typed]));
+            return;
+        }
+
+        let mut picked = None;
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none())
+            .show(ctx, |ui| {
+                self.ui.draw_main_window(
+                    ui,
+                    &mut self.query,
+                    &self.results,
+                    &ScanProgress::default(),
+                    self.animation_time,
+                    |item| picked = Some(item.path.clone()),
+                );
+            });
+
+        if let Some(item) = picked {
+            let selection = if self.marked.is_empty() { vec![item] } else { self.marked.clone() };
+            self.finish(ctx, Some(selection));
+        }
+
+        ctx.request_repaint_after(Duration::from_millis(16)); // ~60 FPS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Selection = Arc<Mutex<Option<Vec<String>>>>;
+
+    fn new_picker(items: &[&str], options: DmenuOptions) -> (DmenuPicker, Selection) {
+        let selection = Arc::new(Mutex::new(None));
+        let items = items.iter().map(|item| item.to_string()).collect();
+        let picker = DmenuPicker::new(&Config::default(), items, options, Arc::clone(&selection));
+        (picker, selection)
+    }
+
+    /// Names of the items `query` matches, best first
+    fn matches(picker: &mut DmenuPicker, query: &str) -> Vec<String> {
+        picker.query = query.to_string();
+        picker.filter().into_iter().map(|item| item.name).collect()
+    }
+
+    /// Run one frame of the picker with `keys` pressed
+    fn frame(picker: &mut DmenuPicker, ctx: &egui::Context, keys: &[egui::Key]) {
+        let events = keys
+            .iter()
+            .map(|&key| egui::Event::Key {
+                key,
+                physical_key: None,
+                pressed: true,
+                repeat: false,
+                modifiers: egui::Modifiers::NONE,
+            })
+            .collect();
+        let input = egui::RawInput {
+            events,
+            ..Default::default()
+        };
+        let _ = ctx.run(input, |ctx| picker.show(ctx));
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn filters_items_best_first() {
+        let (mut picker, _) = new_picker(&["firefox", "Files", "thunderbird", "gimp"], DmenuOptions::default());
+
+        assert_eq!(matches(&mut picker, ""), ["firefox", "Files", "thunderbird", "gimp"]);
+        assert_eq!(matches(&mut picker, "fi"), ["firefox", "Files"]);
+        assert_eq!(matches(&mut picker, "FIREF"), ["firefox"]);
+        assert!(matches(&mut picker, "zzz").is_empty());
+
+        picker.enable_fuzzy_search = false;
+        assert_eq!(matches(&mut picker, "ird"), ["thunderbird"]);
+        assert!(matches(&mut picker, "tbird").is_empty());
+    }
+
+    #[test]
+    fn case_sensitive_mode_finds_any_same_case_match() {
+        let options = DmenuOptions::parse(&args(&["-s"])).unwrap();
+        let (mut picker, _) = new_picker(&["aA", "fooFOO", "foo", "Abc"], options);
+
+        // The case-insensitive alignment would pick the first `a` and `foo`
+        assert_eq!(matches(&mut picker, "A"), ["Abc", "aA"]);
+        assert_eq!(matches(&mut picker, "FOO"), ["fooFOO"]);
+        assert_eq!(picker.filter()[0].name_matches, vec![3..6]);
+
+        picker.enable_fuzzy_search = false;
+        assert_eq!(matches(&mut picker, "FOO"), ["fooFOO"]);
+        assert_eq!(picker.filter()[0].name_matches, vec![3..6]);
+        assert!(matches(&mut picker, "ABC").is_empty());
+    }
+
+    #[test]
+    fn enter_picks_the_highlighted_item_or_the_typed_text() {
+        let ctx = egui::Context::default();
+        let (mut picker, selection) = new_picker(&["alpha", "beta", "gamma"], DmenuOptions::default());
+        picker.query = "a".to_string();
+        frame(&mut picker, &ctx, &[]);
+        frame(&mut picker, &ctx, &[egui::Key::ArrowDown]);
+        frame(&mut picker, &ctx, &[egui::Key::Enter]);
+        assert_eq!(*selection.lock().unwrap(), Some(args(&["beta"])));
+
+        let (mut picker, selection) = new_picker(&["alpha"], DmenuOptions::default());
+        picker.query = "  new item ".to_string();
+        frame(&mut picker, &ctx, &[]);
+        frame(&mut picker, &ctx, &[egui::Key::Enter]);
+        assert_eq!(*selection.lock().unwrap(), Some(args(&["new item"])));
+    }
+
+    #[test]
+    fn escape_cancels() {
+        let ctx = egui::Context::default();
+        let (mut picker, selection) = new_picker(&["alpha"], DmenuOptions::default());
+        frame(&mut picker, &ctx, &[egui::Key::Escape]);
+        assert_eq!(*selection.lock().unwrap(), None);
+    }
+
+    #[test]
+    fn tab_marks_items_in_multi_select_mode() {
+        let ctx = egui::Context::default();
+        let options = DmenuOptions::parse(&args(&["--multi-select"])).unwrap();
+        let (mut picker, selection) = new_picker(&["alpha", "beta", "gamma"], options);
+
+        frame(&mut picker, &ctx, &[egui::Key::Tab]);
+        frame(&mut picker, &ctx, &[egui::Key::ArrowDown]);
+        frame(&mut picker, &ctx, &[egui::Key::ArrowDown]);
+        frame(&mut picker, &ctx, &[egui::Key::Tab]);
+        frame(&mut picker, &ctx, &[egui::Key::ArrowUp]);
+        frame(&mut picker, &ctx, &[egui::Key::Tab]);
+        assert_eq!(picker.marked, ["alpha", "gamma", "beta"]);
+
+        // A second Tab unmarks
+        frame(&mut picker, &ctx, &[egui::Key::Tab]);
+        assert_eq!(picker.marked, ["alpha", "gamma"]);
+
+        // Enter prints every marked item, not the highlighted one
+        frame(&mut picker, &ctx, &[egui::Key::Enter]);
+        assert_eq!(*selection.lock().unwrap(), Some(args(&["alpha", "gamma"])));
+    }
+
+    #[test]
+    fn tab_does_not_mark_without_multi_select() {
+        let ctx = egui::Context::default();
+        let (mut picker, _) = new_picker(&["alpha"], DmenuOptions::default());
+        frame(&mut picker, &ctx, &[egui::Key::Tab]);
+        assert!(picker.marked.is_empty());
+    }
+
+    #[test]
+    fn prints_the_selection_and_returns_the_exit_code() {
+        let mut out = Vec::new();
+        assert_eq!(DmenuPicker::print_selection(Some(args(&["a b", "c"])), &mut out), 0);
+        assert_eq!(String::from_utf8(out).unwrap(), "a b\nc\n");
+
+        let mut out = Vec::new();
+        assert_eq!(DmenuPicker::print_selection(None, &mut out), 1);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn parses_options() {
+        let options = DmenuOptions::parse(&args(&["-p", "Run:", "-s", "-m"])).unwrap();
+        assert_eq!(options.prompt, "Run:");
+        assert!(options.case_sensitive && options.multi_select);
+
+        assert!(!DmenuOptions::parse(&args(&["-s", "-i"])).unwrap().case_sensitive);
+        assert_eq!(DmenuOptions::parse(&args(&["--prompt"])).unwrap_err(), "--prompt needs a value");
+        assert_eq!(DmenuOptions::parse(&args(&["-x"])).unwrap_err(), "Unknown option \"-x\"");
+    }
+}
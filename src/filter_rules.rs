@@ -0,0 +1,372 @@
+use crate::app_info::AppInfo;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How a rule's pattern is matched. All kinds ignore case.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleKind {
+    #[default]
+    Substring,
+    Glob, // `*` and `?` stay within one path component, `**` crosses them
+    Regex,
+}
+
+/// What a rule's pattern is matched against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleTarget {
+    #[default]
+    Name, // Display name of the app
+    Path, // Full path of the file, or of its shortcut target
+}
+
+/// An include or exclude rule from `SearchConfig`
+/// A plain string in the config is a substring rule on the name, so
+/// `"unins"` and `{ "pattern": "unins" }` mean the same thing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RuleSpec")]
+pub struct FilterRule {
+    pub pattern: String,
+    pub kind: RuleKind,
+    pub target: RuleTarget,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RuleSpec {
+    Pattern(String),
+    Rule {
+        pattern: String,
+        #[serde(default)]
+        kind: RuleKind,
+        #[serde(default)]
+        target: RuleTarget,
+    },
+}
+
+impl From<RuleSpec> for FilterRule {
+    fn from(spec: RuleSpec) -> Self {
+        match spec {
+            RuleSpec::Pattern(pattern) => Self::substring(&pattern),
+            RuleSpec::Rule { pattern, kind, target } => Self { pattern, kind, target },
+        }
+    }
+}
+
+impl FilterRule {
+    /// Substring rule on the app name
+    pub fn substring(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            kind: RuleKind::Substring,
+            target: RuleTarget::Name,
+        }
+    }
+
+    /// Why the pattern can't be compiled, if it can't
+    pub fn check(&self) -> Result<(), String> {
+        CompiledRule::matcher(self).map(|_| ()).map_err(|e| e.to_string())
+    }
+}
+
+impl fmt::Display for FilterRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            RuleKind::Substring => "substring",
+            RuleKind::Glob => "glob",
+            RuleKind::Regex => "regex",
+        };
+        let target = match self.target {
+            RuleTarget::Name => "name",
+            RuleTarget::Path => "path",
+        };
+        write!(f, "{} {:?} on {}", kind, self.pattern, target)
+    }
+}
+
+/// Outcome of checking one app against the rules
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterVerdict {
+    Kept,
+    Included(FilterRule), // Kept by an include rule, even if an exclude rule matched
+    Excluded(FilterRule),
+}
+
+impl fmt::Display for FilterVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterVerdict::Kept => write!(f, "kept: no rule matches"),
+            FilterVerdict::Included(rule) => write!(f, "kept by include rule {}", rule),
+            FilterVerdict::Excluded(rule) => write!(f, "hidden by exclude rule {}", rule),
+        }
+    }
+}
+
+/// Compiled include and exclude rules
+/// An app is hidden when it matches any exclude rule, unless it also matches an
+/// include rule, so includes can rescue apps caught by a broad exclude.
+#[derive(Debug, Clone, Default)]
+pub struct FilterRules {
+    include: Vec<CompiledRule>,
+    exclude: Vec<CompiledRule>,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    rule: FilterRule,
+    matcher: Matcher,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Substring(String), // Lowercased pattern
+    Regex(Regex),
+}
+
+impl FilterRules {
+    /// Compile the rules; invalid patterns are reported and skipped
+    pub fn new(include: &[FilterRule], exclude: &[FilterRule]) -> Self {
+        Self {
+            include: include.iter().filter_map(CompiledRule::compile).collect(),
+            exclude: exclude.iter().filter_map(CompiledRule::compile).collect(),
+        }
+    }
+
+    pub fn include_rules(&self) -> Vec<FilterRule> {
+        self.include.iter().map(|compiled| compiled.rule.clone()).collect()
+    }
+
+    pub fn exclude_rules(&self) -> Vec<FilterRule> {
+        self.exclude.iter().map(|compiled| compiled.rule.clone()).collect()
+    }
+
+    /// Whether `app` belongs in the index
+    pub fn allows(&self, app: &AppInfo) -> bool {
+        !matches!(self.evaluate(app), FilterVerdict::Excluded(_))
+    }
+
+    /// Check `app` against the rules, naming the rule that decided
+    pub fn evaluate(&self, app: &AppInfo) -> FilterVerdict {
+        let Some(excluded_by) = self.exclude.iter().find(|rule| rule.matches(app)) else {
+            return FilterVerdict::Kept;
+        };
+
+        match self.include.iter().find(|rule| rule.matches(app)) {
+            Some(included_by) => FilterVerdict::Included(included_by.rule.clone()),
+            None => FilterVerdict::Excluded(excluded_by.rule.clone()),
+        }
+    }
+}
+
+impl CompiledRule {
+    fn compile(rule: &FilterRule) -> Option<Self> {
+        match Self::matcher(rule) {
+            Ok(matcher) => Some(Self {
+                rule: rule.clone(),
+                matcher,
+            }),
+            Err(e) => {
+                eprintln!("Ignoring invalid filter rule {}: {}", rule, e);
+                None
+            }
+        }
+    }
+
+    fn matcher(rule: &FilterRule) -> Result<Matcher, regex::Error> {
+        match rule.kind {
+            RuleKind::Substring => Ok(Matcher::Substring(rule.pattern.to_lowercase())),
+            RuleKind::Glob => Self::regex(&Self::glob_to_regex(&rule.pattern)),
+            RuleKind::Regex => Self::regex(&rule.pattern),
+        }
+    }
+
+    fn regex(pattern: &str) -> Result<Matcher, regex::Error> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(Matcher::Regex)
+    }
+
+    /// Translate a glob into an anchored regex
+    fn glob_to_regex(glob: &str) -> String {
+        let mut regex = String::from("^");
+        let mut chars = glob.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    regex.push_str(".*");
+                }
+                '*' => regex.push_str(r"[^/\\]*"),
+                '?' => regex.push_str(r"[^/\\]"),
+                _ => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+
+        regex.push('$');
+        regex
+    }
+
+    fn matches(&self, app: &AppInfo) -> bool {
+        match self.rule.target {
+            RuleTarget::Name => self.matches_text(&app.name),
+            RuleTarget::Path => {
+                self.matches_text(&app.path)
+                    || app.target.as_deref().is_some_and(|target| self.matches_text(target))
+            }
+        }
+    }
+
+    fn matches_text(&self, text: &str) -> bool {
+        match &self.matcher {
+            Matcher::Substring(pattern) => text.to_lowercase().contains(pattern),
+            Matcher::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SearchConfig;
+
+    /// Index entry named `name` for the file at `path`
+    fn app(name: &str, path: &str, target: Option<&str>) -> AppInfo {
+        AppInfo {
+            name: name.to_string(),
+            path: path.to_string(),
+            target: target.map(str::to_string),
+            sources: vec![path.to_string()],
+            path_hint: None,
+            icon_path: None,
+            description: None,
+            hotkey: None,
+            keywords: Vec::new(),
+            version_info: None,
+            score: 0.0,
+            name_matches: Vec::new(),
+            path_matches: Vec::new(),
+        }
+    }
+
+    fn rule(pattern: &str, kind: RuleKind, target: RuleTarget) -> FilterRule {
+        FilterRule {
+            pattern: pattern.to_string(),
+            kind,
+            target,
+        }
+    }
+
+    #[test]
+    fn globs_translate_to_anchored_regexes() {
+        assert_eq!(CompiledRule::glob_to_regex("a*b"), r"^a[^/\\]*b$");
+        assert_eq!(CompiledRule::glob_to_regex("**/x?"), r"^.*/x[^/\\]$");
+        assert_eq!(CompiledRule::glob_to_regex("v1.0+"), r"^v1\.0\+$");
+    }
+
+    #[test]
+    fn glob_wildcards_stay_within_a_path_component() {
+        let single = FilterRules::new(&[], &[rule("/opt/*/bin/*", RuleKind::Glob, RuleTarget::Path)]);
+        assert!(!single.allows(&app("Tool", "/opt/tool/bin/tool", None)));
+        assert!(single.allows(&app("Tool", "/opt/tool/lib/bin/tool", None)));
+
+        let any = FilterRules::new(&[], &[rule("/opt/**/tool", RuleKind::Glob, RuleTarget::Path)]);
+        assert!(!any.allows(&app("Tool", "/opt/tool/lib/bin/tool", None)));
+        assert!(any.allows(&app("Tool", "/usr/bin/tool", None)));
+    }
+
+    #[test]
+    fn all_kinds_ignore_case() {
+        let rules = FilterRules::new(
+            &[],
+            &[
+                rule("SETUP", RuleKind::Substring, RuleTarget::Name),
+                rule("*.TMP", RuleKind::Glob, RuleTarget::Name),
+                rule("^uninstall", RuleKind::Regex, RuleTarget::Name),
+            ],
+        );
+
+        assert!(!rules.allows(&app("Game Setup", "/a", None)));
+        assert!(!rules.allows(&app("cache.tmp", "/b", None)));
+        assert!(!rules.allows(&app("Uninstall Game", "/c", None)));
+        assert!(rules.allows(&app("Game", "/d", None)));
+    }
+
+    #[test]
+    fn name_and_path_rules_match_different_text() {
+        let name_rule = FilterRules::new(&[], &[FilterRule::substring("beta")]);
+        assert!(!name_rule.allows(&app("Beta Tool", "/opt/tool", None)));
+        assert!(name_rule.allows(&app("Tool", "/opt/beta/tool", None)));
+
+        // Path rules also see the target a shortcut resolves to
+        let path_rule = FilterRules::new(&[], &[rule("beta", RuleKind::Substring, RuleTarget::Path)]);
+        assert!(path_rule.allows(&app("Beta Tool", "/opt/tool", None)));
+        assert!(!path_rule.allows(&app("Tool", "/opt/beta/tool", None)));
+        assert!(!path_rule.allows(&app("Tool", "/usr/bin/tool", Some("/opt/beta/tool"))));
+    }
+
+    #[test]
+    fn include_rules_override_excludes() {
+        let install = FilterRule::substring("install");
+        let keep = rule("Package Installer", RuleKind::Glob, RuleTarget::Name);
+        let rules = FilterRules::new(std::slice::from_ref(&keep), std::slice::from_ref(&install));
+
+        assert_eq!(rules.evaluate(&app("Game Installer", "/a", None)), FilterVerdict::Excluded(install.clone()));
+        assert_eq!(rules.evaluate(&app("Package Installer", "/b", None)), FilterVerdict::Included(keep.clone()));
+        assert_eq!(rules.evaluate(&app("Editor", "/c", None)), FilterVerdict::Kept);
+
+        // Include rules alone don't hide anything
+        let includes_only = FilterRules::new(&[keep], &[]);
+        assert_eq!(includes_only.evaluate(&app("Editor", "/c", None)), FilterVerdict::Kept);
+    }
+
+    #[test]
+    fn verdicts_name_the_deciding_rule() {
+        let exclude = rule("*crash*", RuleKind::Glob, RuleTarget::Path);
+        let include = FilterRule::substring("reporter");
+
+        assert_eq!(FilterVerdict::Kept.to_string(), "kept: no rule matches");
+        assert_eq!(
+            FilterVerdict::Excluded(exclude).to_string(),
+            r#"hidden by exclude rule glob "*crash*" on path"#
+        );
+        assert_eq!(
+            FilterVerdict::Included(include).to_string(),
+            r#"kept by include rule substring "reporter" on name"#
+        );
+    }
+
+    #[test]
+    fn invalid_patterns_are_reported_and_skipped() {
+        let broken = rule("(unclosed", RuleKind::Regex, RuleTarget::Name);
+        assert!(broken.check().is_err());
+        assert!(FilterRule::substring("(unclosed").check().is_ok());
+
+        let rules = FilterRules::new(&[], &[broken, FilterRule::substring("setup")]);
+        assert_eq!(rules.exclude_rules(), [FilterRule::substring("setup")]);
+    }
+
+    #[test]
+    fn plain_strings_are_substring_rules_on_the_name() {
+        let rules: Vec<FilterRule> =
+            serde_json::from_str(r#"["unins", {"pattern": "*.bak", "kind": "glob", "target": "path"}]"#).unwrap();
+
+        assert_eq!(rules[0], FilterRule::substring("unins"));
+        assert_eq!(rules[1], rule("*.bak", RuleKind::Glob, RuleTarget::Path));
+    }
+
+    #[test]
+    fn default_excludes_only_hide_whole_words() {
+        let defaults = SearchConfig::default();
+        let rules = FilterRules::new(&defaults.include_patterns, &defaults.exclude_patterns);
+
+        assert!(!rules.allows(&app("Google Chrome Helper", "/opt/chrome/helper", None)));
+        assert!(!rules.allows(&app("Bluetooth Daemon", "/usr/bin/bluetoothd", None)));
+        assert!(!rules.allows(&app("Crash Reporter", "/usr/lib/app/crash_reporter", None)));
+        assert!(rules.allows(&app("Daemon Tools", "/opt/daemon-tools/run", None)));
+        assert!(rules.allows(&app("Helperbird", "/usr/bin/helperbird", None)));
+    }
+}
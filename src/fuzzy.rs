@@ -0,0 +1,378 @@
+use std::ops::Range;
+
+/// Result of a successful fuzzy match
+#[derive(Clone, Debug)]
+pub struct FuzzyMatch {
+    pub score: f32,
+    /// Character indices of the candidate that matched the query, ascending
+    pub positions: Vec<usize>,
+}
+
+/// Fuzzy string matching for application search
+/// Scores a query as a subsequence of the candidate, rewarding word starts and
+/// consecutive runs, and falls back to a typo-tolerant comparison when the query
+/// is not a subsequence at all
+pub struct FuzzyMatcher;
+
+impl FuzzyMatcher {
+    // Base score for every matched query character
+    const SCORE_MATCH: i32 = 16;
+
+    // Penalties for skipped candidate characters between two matches
+    const PENALTY_GAP_START: i32 = 3;
+    const PENALTY_GAP_EXTENSION: i32 = 1;
+
+    // Bonuses for where a character matched
+    const BONUS_BOUNDARY: i32 = 8;
+    const BONUS_CAMEL_CASE: i32 = 7;
+    const BONUS_CONSECUTIVE: i32 = 4;
+    const BONUS_FIRST_CHAR_MULTIPLIER: i32 = 2;
+
+    // Queries shorter than this never use the typo fallback
+    const TYPO_MIN_QUERY_LEN: usize = 3;
+
+    /// Matches `query` against `candidate`, returning `None` if nothing matched.
+    /// Matching is case-insensitive; higher scores are better.
+    pub fn fuzzy_match(candidate: &str, query: &str) -> Option<FuzzyMatch> {
+        Self::fuzzy_match_folded(candidate, query, Self::fold_case)
+    }
+
+    /// [`Self::fuzzy_match`] where only letters of the same case match
+    pub fn fuzzy_match_case_sensitive(candidate: &str, query: &str) -> Option<FuzzyMatch> {
+        Self::fuzzy_match_folded(candidate, query, |c| c)
+    }
+
+    /// Fuzzy match comparing characters after mapping them with `fold`
+    fn fuzzy_match_folded(candidate: &str, query: &str, fold: fn(char) -> char) -> Option<FuzzyMatch> {
+        let candidate_chars: Vec<char> = candidate.chars().collect();
+        let query_chars: Vec<char> = query
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(fold)
+            .collect();
+
+        if query_chars.is_empty() || candidate_chars.is_empty() {
+            return None;
+        }
+
+        Self::subsequence_score(&candidate_chars, &query_chars, fold)
+            .or_else(|| Self::typo_score(&candidate_chars, &query_chars, fold))
+            .map(|(score, positions)| FuzzyMatch {
+                score: score as f32,
+                positions,
+            })
+    }
+
+    /// Case-insensitive substring search returning the character range of the first hit
+    pub fn substring_range(candidate: &str, query: &str) -> Option<Range<usize>> {
+        Self::substring_range_folded(candidate, query, Self::fold_case)
+    }
+
+    /// [`Self::substring_range`] where only letters of the same case match
+    pub fn substring_range_case_sensitive(candidate: &str, query: &str) -> Option<Range<usize>> {
+        Self::substring_range_folded(candidate, query, |c| c)
+    }
+
+    fn substring_range_folded(candidate: &str, query: &str, fold: fn(char) -> char) -> Option<Range<usize>> {
+        let candidate: Vec<char> = candidate.chars().map(fold).collect();
+        let query: Vec<char> = query.chars().map(fold).collect();
+
+        if query.is_empty() || query.len() > candidate.len() {
+            return None;
+        }
+
+        candidate
+            .windows(query.len())
+            .position(|window| window == query.as_slice())
+            .map(|start| start..start + query.len())
+    }
+
+    /// Collapses sorted character positions into contiguous ranges for highlighting
+    pub fn positions_to_ranges(positions: &[usize]) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+
+        for &position in positions {
+            match ranges.last_mut() {
+                Some(last) if last.end == position => last.end += 1,
+                _ => ranges.push(position..position + 1),
+            }
+        }
+
+        ranges
+    }
+
+    /// Best-scoring alignment of the query as a subsequence of the candidate.
+    /// Runs in O(query * candidate) using a running maximum for gapped transitions,
+    /// keeping each row's predecessors so the matched positions can be recovered.
+    fn subsequence_score(candidate: &[char], query: &[char], fold: fn(char) -> char) -> Option<(i32, Vec<usize>)> {
+        let n = candidate.len();
+        let m = query.len();
+        if m > n {
+            return None;
+        }
+
+        let folded: Vec<char> = candidate.iter().copied().map(fold).collect();
+        let bonuses: Vec<i32> = (0..n).map(|j| Self::position_bonus(candidate, j)).collect();
+
+        // Quick reject before allocating the score rows
+        let mut next = 0;
+        for &c in &folded {
+            if next < m && c == query[next] {
+                next += 1;
+            }
+        }
+        if next < m {
+            return None;
+        }
+
+        // rows[i][j]: best (score, predecessor) with query[..=i] matched and query[i] at candidate[j]
+        let mut rows: Vec<Vec<Option<(i32, usize)>>> = Vec::with_capacity(m);
+
+        let mut first = vec![None; n];
+        for j in 0..n {
+            if folded[j] == query[0] {
+                first[j] = Some((Self::SCORE_MATCH + bonuses[j] * Self::BONUS_FIRST_CHAR_MULTIPLIER, j));
+            }
+        }
+        rows.push(first);
+
+        for &query_char in &query[1..] {
+            let previous = &rows[rows.len() - 1];
+            let mut current = vec![None; n];
+            // Best (score, position) of a match at k < j - 1, already charged for the gap up to j
+            let mut gapped: Option<(i32, usize)> = None;
+
+            for j in 1..n {
+                if j >= 2 {
+                    let extended = gapped.map(|(s, k)| (s - Self::PENALTY_GAP_EXTENSION, k));
+                    let started = previous[j - 2].map(|(s, _)| (s - Self::PENALTY_GAP_START, j - 2));
+                    gapped = extended.max(started);
+                }
+
+                if folded[j] != query_char {
+                    continue;
+                }
+
+                let consecutive = previous[j - 1]
+                    .map(|(s, _)| (s + Self::BONUS_CONSECUTIVE + bonuses[j].max(0), j - 1));
+                let after_gap = gapped.map(|(s, k)| (s + bonuses[j], k));
+
+                current[j] = consecutive.max(after_gap).map(|(s, k)| (s + Self::SCORE_MATCH, k));
+            }
+
+            rows.push(current);
+        }
+
+        let (end, (score, _)) = rows[m - 1]
+            .iter()
+            .enumerate()
+            .filter_map(|(j, cell)| cell.map(|c| (j, c)))
+            .max_by_key(|(_, (score, _))| *score)?;
+
+        // Walk the predecessors back to the first query character
+        let mut positions = vec![0; m];
+        let mut j = end;
+        for i in (0..m).rev() {
+            positions[i] = j;
+            if let Some((_, from)) = rows[i][j] {
+                j = from;
+            }
+        }
+
+        Some((score, positions))
+    }
+
+    /// Typo-tolerant fallback: compares the query against candidate slices that begin
+    /// on a word boundary, allowing a small number of edits (including transpositions)
+    fn typo_score(candidate: &[char], query: &[char], fold: fn(char) -> char) -> Option<(i32, Vec<usize>)> {
+        let m = query.len();
+        if m < Self::TYPO_MIN_QUERY_LEN {
+            return None;
+        }
+
+        let max_typos = if m >= 8 { 2 } else { 1 };
+        let folded: Vec<char> = candidate.iter().copied().map(fold).collect();
+
+        let mut best: Option<(usize, Range<usize>)> = None;
+        for start in 0..folded.len() {
+            if start > 0 && Self::position_bonus(candidate, start) == 0 {
+                continue;
+            }
+
+            for len in (m - 1)..=(m + 1) {
+                if start + len > folded.len() {
+                    break;
+                }
+
+                let distance = Self::edit_distance(query, &folded[start..start + len]);
+                if distance <= max_typos && best.as_ref().is_none_or(|(b, _)| distance < *b) {
+                    best = Some((distance, start..start + len));
+                }
+            }
+        }
+
+        let (distance, range) = best?;
+        let score = (Self::SCORE_MATCH * m as i32) / 2 - Self::SCORE_MATCH * distance as i32;
+
+        (score > 0).then(|| (score, range.collect()))
+    }
+
+    /// Optimal string alignment distance (Levenshtein plus adjacent transpositions)
+    fn edit_distance(a: &[char], b: &[char]) -> usize {
+        let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            row[0] = i;
+        }
+        for (j, cell) in rows[0].iter_mut().enumerate() {
+            *cell = j;
+        }
+
+        for i in 1..=a.len() {
+            for j in 1..=b.len() {
+                let cost = usize::from(a[i - 1] != b[j - 1]);
+                let mut value = (rows[i - 1][j] + 1)
+                    .min(rows[i][j - 1] + 1)
+                    .min(rows[i - 1][j - 1] + cost);
+
+                if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                    value = value.min(rows[i - 2][j - 2] + 1);
+                }
+
+                rows[i][j] = value;
+            }
+        }
+
+        rows[a.len()][b.len()]
+    }
+
+    /// Bonus for matching at `index`: word starts and camelCase humps score higher
+    fn position_bonus(chars: &[char], index: usize) -> i32 {
+        let current = chars[index];
+        if index == 0 {
+            return Self::BONUS_BOUNDARY;
+        }
+
+        let previous = chars[index - 1];
+        if Self::is_separator(previous) && !Self::is_separator(current) {
+            Self::BONUS_BOUNDARY
+        } else if (previous.is_lowercase() && current.is_uppercase())
+            || (previous.is_alphabetic() && current.is_ascii_digit())
+        {
+            Self::BONUS_CAMEL_CASE
+        } else {
+            0
+        }
+    }
+
+    fn is_separator(c: char) -> bool {
+        c.is_whitespace() || matches!(c, '-' | '_' | '.' | '/' | '\\' | '(' | ')' | '[' | ']' | ':' | ',')
+    }
+
+    fn fold_case(c: char) -> char {
+        c.to_lowercase().next().unwrap_or(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(candidate: &str, query: &str) -> f32 {
+        FuzzyMatcher::fuzzy_match(candidate, query).map_or(0.0, |m| m.score)
+    }
+
+    fn positions(candidate: &str, query: &str) -> Vec<usize> {
+        FuzzyMatcher::fuzzy_match(candidate, query).unwrap().positions
+    }
+
+    #[test]
+    fn matches_subsequences_case_insensitively() {
+        assert_eq!(positions("Visual Studio Code", "vscde"), [0, 7, 14, 16, 17]);
+        assert_eq!(positions("Google Chrome", "GC"), [0, 7]);
+        assert_eq!(positions("Firefox", "fire fox"), [0, 1, 2, 3, 4, 5, 6]);
+        assert!(FuzzyMatcher::fuzzy_match("Firefox", "").is_none());
+        assert!(FuzzyMatcher::fuzzy_match("", "a").is_none());
+        assert!(FuzzyMatcher::fuzzy_match("Firefox", "zz").is_none());
+    }
+
+    #[test]
+    fn prefers_word_starts_and_consecutive_runs() {
+        // `gc` lines up with both word starts instead of the first `g` and `c`
+        assert!(score("Google Chrome", "gc") > score("Magic", "gc"));
+        assert!(score("GitKraken", "gk") > score("Gnome Tweak", "gk"));
+        assert!(score("Firefox", "fire") > score("File Reader Extra", "fire"));
+        assert!(score("Terminal", "term") > score("Thunderbird Email", "term"));
+
+        // The best alignment is chosen, not the first one found
+        assert_eq!(positions("Code - OSS", "cos"), [0, 7, 8]);
+        assert_eq!(positions("acode", "code"), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn ranks_closer_matches_higher() {
+        let query = "chrome";
+        let mut candidates = ["Chromium", "Google Chrome", "Cheese Recorder Home", "Chrome Remote Desktop"];
+        candidates.sort_by(|a, b| score(b, query).total_cmp(&score(a, query)));
+
+        assert_eq!(candidates[3], "Chromium");
+        assert_eq!(candidates[2], "Cheese Recorder Home");
+        assert!(score("Chromium", query) > 0.0);
+    }
+
+    #[test]
+    fn falls_back_to_typos_at_word_starts() {
+        // Transposed letters aren't a subsequence, but are one edit away
+        let typo = FuzzyMatcher::fuzzy_match("Firefox", "fierfox").unwrap();
+        assert_eq!(typo.positions, [0, 1, 2, 3, 4, 5, 6]);
+        assert!(typo.score < score("Firefox", "firefox"));
+
+        assert_eq!(positions("Mozilla Thunderbird", "thunderbrid"), (8..19).collect::<Vec<_>>());
+        assert_eq!(positions("Gimp", "gmip"), [0, 1, 2, 3]);
+
+        // Short queries and too many edits don't match
+        assert!(FuzzyMatcher::fuzzy_match("Firefox", "xf").is_none());
+        assert!(FuzzyMatcher::fuzzy_match("Firefox", "fxrefxx").is_none());
+        // Typos only count from a word start
+        assert!(FuzzyMatcher::fuzzy_match("Bitwarden", "wrdan").is_none());
+    }
+
+    #[test]
+    fn edit_distance_counts_transpositions_once() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(FuzzyMatcher::edit_distance(&chars("abc"), &chars("abc")), 0);
+        assert_eq!(FuzzyMatcher::edit_distance(&chars("abc"), &chars("acb")), 1);
+        assert_eq!(FuzzyMatcher::edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(FuzzyMatcher::edit_distance(&chars(""), &chars("abc")), 3);
+    }
+
+    #[test]
+    fn ranges_count_characters_not_bytes() {
+        assert_eq!(FuzzyMatcher::substring_range("Café Éditeur", "édit"), Some(5..9));
+        assert_eq!(FuzzyMatcher::substring_range("ÜBERSICHT", "übersicht"), Some(0..9));
+        assert_eq!(FuzzyMatcher::substring_range("日本語エディタ", "エディ"), Some(3..6));
+        assert_eq!(FuzzyMatcher::substring_range("🦀 Rust", "rust"), Some(2..6));
+        assert_eq!(FuzzyMatcher::substring_range("Café", "cafés"), None);
+        assert_eq!(FuzzyMatcher::substring_range("Café", ""), None);
+
+        assert_eq!(positions("Café Éditeur", "cé"), [0, 5]);
+        assert_eq!(positions("日本語エディタ", "本エタ"), [1, 3, 6]);
+        assert_eq!(positions("Ünïcødé Editor", "ed"), [8, 9]);
+    }
+
+    #[test]
+    fn positions_collapse_into_highlight_ranges() {
+        assert!(FuzzyMatcher::positions_to_ranges(&[]).is_empty());
+        assert_eq!(FuzzyMatcher::positions_to_ranges(&[3, 4]), vec![3..5]);
+        assert_eq!(FuzzyMatcher::positions_to_ranges(&[0, 1, 2, 5, 7, 8]), [0..3, 5..6, 7..9]);
+
+        // Ranges index the name's characters, so slicing by them lands on whole characters
+        let name = "Café Éditeur";
+        let ranges = FuzzyMatcher::positions_to_ranges(&positions(name, "éédi"));
+        let highlighted: Vec<String> = ranges
+            .iter()
+            .map(|range| name.chars().skip(range.start).take(range.len()).collect())
+            .collect();
+        assert_eq!(highlighted, ["é", "Édi"]);
+    }
+}
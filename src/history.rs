@@ -0,0 +1,238 @@
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Launch statistics for a single application, keyed by its path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchStats {
+    pub name: String,
+    pub launch_count: u32,
+    pub last_launched: u64, // Unix timestamp in seconds
+    pub decayed_score: f64, // Frecency as of `last_launched`
+}
+
+/// Persistent launch history used for frecency ranking and the recent list
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LaunchHistory {
+    entries: HashMap<String, LaunchStats>,
+}
+
+impl LaunchHistory {
+    // Every launch loses half its weight after this long
+    const HALF_LIFE_SECS: f64 = 7.0 * 24.0 * 60.0 * 60.0;
+
+    // Entries with the lowest frecency are dropped beyond this size
+    const MAX_ENTRIES: usize = 500;
+
+    /// Load launch history from disk, starting empty if it is missing or unreadable
+    pub fn load() -> Self {
+        Self::get_history_path()
+            .map(|history_path| Self::load_from(&history_path))
+            .unwrap_or_default()
+    }
+
+    /// Load launch history from `path`, starting empty if it is missing or unreadable
+    pub fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save launch history next to the configuration file
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        match Self::get_history_path() {
+            Some(history_path) => self.save_to(&history_path),
+            None => Ok(()),
+        }
+    }
+
+    /// Save launch history to `path`
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string(self)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    fn get_history_path() -> Option<PathBuf> {
+        Config::get_config_dir().map(|dir| dir.join("history.json"))
+    }
+
+    /// Record a launch of the app at `path`, decaying its previous score to now
+    pub fn record_launch(&mut self, name: &str, path: &str) {
+        self.record_launch_at(name, path, Self::now());
+    }
+
+    /// Record a launch at `now`, a Unix timestamp in seconds
+    fn record_launch_at(&mut self, name: &str, path: &str, now: u64) {
+
+        let stats = self.entries.entry(path.to_string()).or_insert_with(|| LaunchStats {
+            name: name.to_string(),
+            launch_count: 0,
+            last_launched: now,
+            decayed_score: 0.0,
+        });
+
+        stats.decayed_score = Self::decay(stats.decayed_score, stats.last_launched, now) + 1.0;
+        stats.launch_count += 1;
+        stats.last_launched = now;
+        stats.name = name.to_string();
+
+        self.prune(now);
+    }
+
+    /// Current frecency of the app at `path`; 0.0 if it was never launched
+    pub fn frecency(&self, path: &str) -> f64 {
+        self.frecency_at(path, Self::now())
+    }
+
+    fn frecency_at(&self, path: &str, now: u64) -> f64 {
+        self.entries
+            .get(path)
+            .map(|stats| Self::decay(stats.decayed_score, stats.last_launched, now))
+            .unwrap_or(0.0)
+    }
+
+    /// Most recently launched apps first, as `(path, stats)` pairs
+    pub fn most_recent(&self, limit: usize) -> Vec<(&str, &LaunchStats)> {
+        let mut recent: Vec<(&str, &LaunchStats)> = self
+            .entries
+            .iter()
+            .map(|(path, stats)| (path.as_str(), stats))
+            .collect();
+
+        recent.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.last_launched));
+        recent.truncate(limit);
+        recent
+    }
+
+    fn prune(&mut self, now: u64) {
+        if self.entries.len() <= Self::MAX_ENTRIES {
+            return;
+        }
+
+        let mut by_frecency: Vec<(String, f64)> = self
+            .entries
+            .iter()
+            .map(|(path, stats)| (path.clone(), Self::decay(stats.decayed_score, stats.last_launched, now)))
+            .collect();
+
+        by_frecency.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        let excess = self.entries.len() - Self::MAX_ENTRIES;
+        for (path, _) in by_frecency.into_iter().take(excess) {
+            self.entries.remove(&path);
+        }
+    }
+
+    fn decay(score: f64, since: u64, now: u64) -> f64 {
+        let elapsed = now.saturating_sub(since) as f64;
+        score * 0.5f64.powf(elapsed / Self::HALF_LIFE_SECS)
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    // An arbitrary fixed clock
+    const START: u64 = 1_700_000_000;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn frecency_halves_every_week() {
+        let mut history = LaunchHistory::default();
+        history.record_launch_at("Editor", "/usr/bin/editor", START);
+
+        assert_close(history.frecency_at("/usr/bin/editor", START), 1.0);
+        assert_close(history.frecency_at("/usr/bin/editor", START + 7 * DAY), 0.5);
+        assert_close(history.frecency_at("/usr/bin/editor", START + 21 * DAY), 0.125);
+        assert_close(history.frecency_at("/usr/bin/unknown", START), 0.0);
+    }
+
+    #[test]
+    fn launches_add_to_the_decayed_score() {
+        let mut history = LaunchHistory::default();
+        history.record_launch_at("Editor", "/usr/bin/editor", START);
+        history.record_launch_at("Editor", "/usr/bin/editor", START + 7 * DAY);
+
+        assert_close(history.frecency_at("/usr/bin/editor", START + 7 * DAY), 1.5);
+        assert_close(history.frecency_at("/usr/bin/editor", START + 14 * DAY), 0.75);
+
+        let stats = &history.entries["/usr/bin/editor"];
+        assert_eq!(stats.launch_count, 2);
+        assert_eq!(stats.last_launched, START + 7 * DAY);
+    }
+
+    #[test]
+    fn lists_the_most_recent_launches_first() {
+        let mut history = LaunchHistory::default();
+        history.record_launch_at("Old", "/old", START);
+        history.record_launch_at("New", "/new", START + 2);
+        history.record_launch_at("Middle", "/middle", START + 1);
+
+        let recent: Vec<&str> = history.most_recent(2).into_iter().map(|(path, _)| path).collect();
+        assert_eq!(recent, ["/new", "/middle"]);
+    }
+
+    #[test]
+    fn trims_the_least_frecent_entries() {
+        let mut history = LaunchHistory::default();
+
+        // Launched often long ago, so it decays below the rest
+        for _ in 0..4 {
+            history.record_launch_at("Stale", "/stale", START);
+        }
+        for i in 0..LaunchHistory::MAX_ENTRIES as u64 {
+            history.record_launch_at("App", &format!("/app{}", i), START + 60 * DAY + i);
+        }
+
+        assert_eq!(history.entries.len(), LaunchHistory::MAX_ENTRIES);
+        assert!(!history.entries.contains_key("/stale"));
+        assert!(history.entries.contains_key("/app0"));
+    }
+
+    #[test]
+    fn round_trips_through_a_file() {
+        let dir = std::env::temp_dir().join(format!("fade-launcher-history-test-{}", std::process::id()));
+        let path = dir.join("history.json");
+
+        let mut history = LaunchHistory::default();
+        history.record_launch_at("Editor", "/usr/bin/editor", START);
+        history.record_launch_at("Editor", "/usr/bin/editor", START + DAY);
+        history.save_to(&path).unwrap();
+
+        let loaded = LaunchHistory::load_from(&path);
+        let stats = &loaded.entries["/usr/bin/editor"];
+        assert_eq!((stats.name.as_str(), stats.launch_count, stats.last_launched), ("Editor", 2, START + DAY));
+        assert_close(loaded.frecency_at("/usr/bin/editor", START + DAY), history.frecency_at("/usr/bin/editor", START + DAY));
+
+        // A missing or broken file starts an empty history
+        fs::write(&path, "{ not json").unwrap();
+        assert!(LaunchHistory::load_from(&path).entries.is_empty());
+        assert!(LaunchHistory::load_from(&dir.join("missing.json")).entries.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
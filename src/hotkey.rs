@@ -0,0 +1,166 @@
+/// A hotkey setting such as "Ctrl+Shift+K", parsed without a GUI toolkit so
+/// the config can be checked headless. The window turns it into an egui shortcut.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hotkey {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub command: bool, // Cmd on macOS, Ctrl elsewhere
+    pub key: String, // Canonical key name, e.g. "Enter", "K" or "F5"
+}
+
+impl Hotkey {
+    /// Named keys and their aliases. Letters, digits and F1-F20 are handled
+    /// separately. The names are the ones egui knows.
+    const NAMED_KEYS: &'static [(&'static str, &'static [&'static str])] = &[
+        ("Down", &["⏷", "ArrowDown"]),
+        ("Left", &["⏴", "ArrowLeft"]),
+        ("Right", &["⏵", "ArrowRight"]),
+        ("Up", &["⏶", "ArrowUp"]),
+        ("Escape", &["Esc"]),
+        ("Tab", &[]),
+        ("Backspace", &[]),
+        ("Enter", &["Return"]),
+        ("Insert", &["Help"]),
+        ("Delete", &[]),
+        ("Home", &[]),
+        ("End", &[]),
+        ("PageUp", &[]),
+        ("PageDown", &[]),
+        ("Copy", &[]),
+        ("Cut", &[]),
+        ("Paste", &[]),
+        ("Space", &[" "]),
+        ("Colon", &[":"]),
+        ("Comma", &[","]),
+        ("Minus", &["-", "−"]),
+        ("Period", &["."]),
+        ("Plus", &["+"]),
+        ("Equals", &["=", "Equal", "NumpadEqual"]),
+        ("Semicolon", &[";"]),
+        ("Backslash", &["\\"]),
+        ("Slash", &["/"]),
+        ("Pipe", &["|"]),
+        ("Questionmark", &["?"]),
+        ("OpenBracket", &["["]),
+        ("CloseBracket", &["]"]),
+        ("Backtick", &["`", "Backquote", "Grave"]),
+    ];
+
+    /// Parse a hotkey such as "Ctrl+Shift+K" or "Enter". Modifier names are
+    /// case-insensitive, key names are not.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut hotkey = Self {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            command: false,
+            key: String::new(),
+        };
+
+        for part in spec.split('+').map(str::trim) {
+            match part.to_lowercase().as_str() {
+                "ctrl" | "control" => hotkey.ctrl = true,
+                "alt" | "option" => hotkey.alt = true,
+                "shift" => hotkey.shift = true,
+                "cmd" | "command" | "super" | "meta" | "win" => hotkey.command = true,
+                _ if hotkey.key.is_empty() => hotkey.key = Self::key_name(part)?,
+                _ => return None,
+            }
+        }
+
+        (!hotkey.key.is_empty()).then_some(hotkey)
+    }
+
+    /// The canonical name of a key, or `None` if there's no such key
+    fn key_name(name: &str) -> Option<String> {
+        if let Some((canonical, _)) = Self::NAMED_KEYS
+            .iter()
+            .find(|(canonical, aliases)| *canonical == name || aliases.contains(&name))
+        {
+            return Some(canonical.to_string());
+        }
+
+        let single = |text: &str, accept: fn(&char) -> bool| {
+            let mut chars = text.chars();
+            chars.next().filter(accept).filter(|_| chars.next().is_none())
+        };
+        if let Some(digit) = name.strip_prefix("Digit").or_else(|| name.strip_prefix("Numpad")) {
+            return single(digit, char::is_ascii_digit).map(String::from);
+        }
+        if let Some(c) = single(name, char::is_ascii_alphanumeric) {
+            return Some(c.to_ascii_uppercase().to_string());
+        }
+
+        let function_key: u8 = name.strip_prefix('F')?.parse().ok()?;
+        ((1..=20).contains(&function_key) && name == format!("F{}", function_key)).then(|| name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A hotkey with the given modifiers, spelled as in a config file
+    fn hotkey(modifiers: &str, key: &str) -> Hotkey {
+        Hotkey {
+            ctrl: modifiers.contains("ctrl"),
+            alt: modifiers.contains("alt"),
+            shift: modifiers.contains("shift"),
+            command: modifiers.contains("cmd"),
+            key: key.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_modifiers_in_any_case_and_order() {
+        assert_eq!(Hotkey::parse("Ctrl+Shift+K"), Some(hotkey("ctrl shift", "K")));
+        assert_eq!(Hotkey::parse("shift + CONTROL + k"), Some(hotkey("ctrl shift", "K")));
+        assert_eq!(Hotkey::parse("Option+Space"), Some(hotkey("alt", "Space")));
+        assert_eq!(Hotkey::parse("Alt+Space"), Some(hotkey("alt", "Space")));
+        for command in ["Cmd", "command", "Super", "META", "Win"] {
+            assert_eq!(Hotkey::parse(&format!("{}+F5", command)), Some(hotkey("cmd", "F5")));
+        }
+    }
+
+    #[test]
+    fn canonicalizes_key_names() {
+        let key = |spec: &str| Hotkey::parse(spec).map(|hotkey| hotkey.key);
+        assert_eq!(key("Enter").as_deref(), Some("Enter"));
+        assert_eq!(key("Return").as_deref(), Some("Enter"));
+        assert_eq!(key("Esc").as_deref(), Some("Escape"));
+        assert_eq!(key("ArrowDown").as_deref(), Some("Down"));
+        assert_eq!(key("Ctrl+,").as_deref(), Some("Comma"));
+        assert_eq!(key("Ctrl+Grave").as_deref(), Some("Backtick"));
+        assert_eq!(key("a").as_deref(), Some("A"));
+        assert_eq!(key("7").as_deref(), Some("7"));
+        assert_eq!(key("Digit7").as_deref(), Some("7"));
+        assert_eq!(key("Numpad0").as_deref(), Some("0"));
+        assert_eq!(key("F1").as_deref(), Some("F1"));
+        assert_eq!(key("F20").as_deref(), Some("F20"));
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_shapes() {
+        for spec in [
+            "",
+            "Ctrl",
+            "Ctrl+Shift",
+            "Ctrl+A+B",
+            "Hyper+A",
+            "enter", // Key names are case-sensitive
+            "esc",
+            "F0",
+            "F21",
+            "F05",
+            "f5",
+            "DigitA",
+            "Numpad10",
+            "AB",
+            "é",
+            "Ctrl+",
+        ] {
+            assert_eq!(Hotkey::parse(spec), None, "{:?} should be rejected", spec);
+        }
+    }
+}
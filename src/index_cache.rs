@@ -0,0 +1,239 @@
+use crate::{
+    app_info::AppInfo,
+    config::{Config, ScanRoot},
+    filter_rules::{FilterRule, FilterRules},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// On-disk snapshot of the application index
+/// Stores the modification time of every directory seen during the scan, and of
+/// the files entries were read from, so a later start can tell whether the
+/// snapshot is still current without walking the scan roots again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexCache {
+    version: u32,
+    scan_roots: Vec<ScanRoot>,
+    file_extensions: Vec<String>,
+    include_patterns: Vec<FilterRule>,
+    exclude_patterns: Vec<FilterRule>,
+    mtimes: Mtimes,
+    pub apps: Vec<AppInfo>,
+}
+
+/// Modification times in milliseconds (0 if missing), by path
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Mtimes {
+    // Adding or removing an entry updates its directory's mtime
+    pub directories: HashMap<PathBuf, u64>,
+    // Editing a file in place doesn't, so every desktop entry and every file
+    // that became an index entry is checked too
+    pub files: HashMap<PathBuf, u64>,
+}
+
+impl Mtimes {
+    pub fn extend(&mut self, other: Mtimes) {
+        self.directories.extend(other.directories);
+        self.files.extend(other.files);
+    }
+}
+
+impl IndexCache {
+    // Bump whenever the cached layout or the meaning of its fields changes
+    const VERSION: u32 = 10;
+
+    pub fn new(
+        scan_roots: &[ScanRoot],
+        file_extensions: &[String],
+        filter_rules: &FilterRules,
+        mtimes: Mtimes,
+        apps: Vec<AppInfo>,
+    ) -> Self {
+        Self {
+            version: Self::VERSION,
+            scan_roots: scan_roots.to_vec(),
+            file_extensions: file_extensions.to_vec(),
+            include_patterns: filter_rules.include_rules(),
+            exclude_patterns: filter_rules.exclude_rules(),
+            mtimes,
+            apps,
+        }
+    }
+
+    /// Load the cache if it exists and was written by this cache version
+    pub fn load() -> Option<Self> {
+        Self::load_from(&Self::get_cache_path()?)
+    }
+
+    /// Load the cache at `path` if it was written by this cache version
+    pub fn load_from(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        let cache: IndexCache = serde_json::from_str(&content).ok()?;
+
+        if cache.version == Self::VERSION {
+            Some(cache)
+        } else {
+            None
+        }
+    }
+
+    /// Save the cache next to the configuration file
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        match Self::get_cache_path() {
+            Some(cache_path) => self.save_to(&cache_path),
+            None => Ok(()),
+        }
+    }
+
+    /// Save the cache to `path`
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string(self)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    fn get_cache_path() -> Option<PathBuf> {
+        // Scans in tests mustn't overwrite the developer's own cache
+        if cfg!(test) {
+            return Some(std::env::temp_dir().join(format!("fade-launcher-index-cache-{}.json", std::process::id())));
+        }
+
+        Config::get_config_dir().map(|dir| dir.join("index-cache.json"))
+    }
+
+    /// Whether this cache was built with the same scan settings and filter rules
+    pub fn matches_settings(
+        &self,
+        scan_roots: &[ScanRoot],
+        file_extensions: &[String],
+        filter_rules: &FilterRules,
+    ) -> bool {
+        self.scan_roots == scan_roots
+            && self.file_extensions == file_extensions
+            && self.include_patterns == filter_rules.include_rules()
+            && self.exclude_patterns == filter_rules.exclude_rules()
+    }
+
+    /// Whether no scanned directory and no indexed file changed since the cache
+    /// was written. This needs one stat per directory and entry instead of a
+    /// full walk.
+    pub fn is_fresh(&self) -> bool {
+        let Mtimes { directories, files } = &self.mtimes;
+        directories
+            .iter()
+            .chain(files)
+            .all(|(path, mtime)| Self::mtime(path) == *mtime)
+    }
+
+    /// Modification time of `path` (of its target, for a symlink) in
+    /// milliseconds, or 0 if it cannot be read
+    pub fn mtime(path: &Path) -> u64 {
+        fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    /// Fresh directory holding one file, `tool.app`
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fade-launcher-cache-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("tool.app"), "").unwrap();
+        dir
+    }
+
+    /// Cache recording `directories` and `files` as they are now
+    fn cache_of(directories: &[&Path], files: &[&Path]) -> IndexCache {
+        let now = |paths: &[&Path]| paths.iter().map(|path| (path.to_path_buf(), IndexCache::mtime(path))).collect();
+        let mtimes = Mtimes {
+            directories: now(directories),
+            files: now(files),
+        };
+        IndexCache::new(&[], &[], &FilterRules::default(), mtimes, Vec::new())
+    }
+
+    #[test]
+    fn files_edited_in_place_make_the_cache_stale() {
+        let dir = temp_dir("edited");
+        let file = dir.join("tool.app");
+        let cache = cache_of(&[&dir], &[&file]);
+        assert!(cache.is_fresh());
+
+        // Rewriting a file doesn't touch its directory
+        let earlier = SystemTime::now() - Duration::from_secs(3600);
+        fs::File::options().write(true).open(&file).unwrap().set_modified(earlier).unwrap();
+        assert!(!cache.is_fresh());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn changed_or_removed_paths_make_the_cache_stale() {
+        let dir = temp_dir("changed");
+        let file = dir.join("tool.app");
+
+        let mut cache = cache_of(&[&dir], &[&file]);
+        cache.mtimes.directories.insert(dir.clone(), 1);
+        assert!(!cache.is_fresh());
+
+        let cache = cache_of(&[&dir], &[&file]);
+        fs::remove_file(&file).unwrap();
+        assert!(!cache.is_fresh());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_roots_stay_fresh_until_they_appear() {
+        let dir = temp_dir("missing");
+        let root = dir.join("later");
+        let cache = cache_of(&[&root], &[]);
+        assert_eq!(cache.mtimes.directories[&root], 0);
+        assert!(cache.is_fresh());
+
+        fs::create_dir(&root).unwrap();
+        assert!(!cache.is_fresh());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn round_trips_through_a_file_of_the_same_version() {
+        let dir = temp_dir("round-trip");
+        let path = dir.join("index-cache.json");
+        let file = dir.join("tool.app");
+
+        let cache = cache_of(&[&dir], &[&file]);
+        cache.save_to(&path).unwrap();
+        let loaded = IndexCache::load_from(&path).unwrap();
+        assert_eq!(loaded.mtimes, cache.mtimes);
+        assert!(loaded.matches_settings(&[], &[], &FilterRules::default()));
+        assert!(!loaded.matches_settings(&[], &[".exe".to_string()], &FilterRules::default()));
+
+        // Caches of another version are rebuilt
+        let mut older: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        older["version"] = (IndexCache::VERSION - 1).into();
+        fs::write(&path, older.to_string()).unwrap();
+        assert!(IndexCache::load_from(&path).is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
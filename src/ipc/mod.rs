@@ -0,0 +1,247 @@
+use crate::rpc::Rpc;
+use serde_json::Value;
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+#[cfg(windows)]
+mod windows;
+#[cfg(unix)]
+mod unix;
+
+#[cfg(windows)]
+use self::windows as backend;
+#[cfg(unix)]
+use self::unix as backend;
+
+/// What a later `fade-launcher` invocation asks the running instance to do
+/// On the wire each command is one line, e.g. `toggle` or `query firefox`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Show,
+    Hide,
+    Toggle,
+    Query(String), // Show the window with this search text
+    Quit,
+}
+
+impl Command {
+    /// The command given by the instance flags, e.g. `--toggle` or `--query TEXT`.
+    /// No arguments at all means show. Anything else is a subcommand.
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        match args.as_slice() {
+            [] | ["--show"] => Some(Self::Show),
+            ["--hide"] => Some(Self::Hide),
+            ["--toggle"] => Some(Self::Toggle),
+            ["--query", text @ ..] if !text.is_empty() => Some(Self::Query(text.join(" "))),
+            ["--quit"] => Some(Self::Quit),
+            _ => None,
+        }
+    }
+
+    /// Parse one line of the wire format
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        let (name, text) = line.split_once(' ').unwrap_or((line, ""));
+
+        match name {
+            "show" => Some(Self::Show),
+            "hide" => Some(Self::Hide),
+            "toggle" => Some(Self::Toggle),
+            "query" => Some(Self::Query(text.trim().to_string())),
+            "quit" => Some(Self::Quit),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Show => write!(f, "show"),
+            Self::Hide => write!(f, "hide"),
+            Self::Toggle => write!(f, "toggle"),
+            // Line breaks would end the command early
+            Self::Query(text) => write!(f, "query {}", text.replace(['\r', '\n'], " ")),
+            Self::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+/// Something sent to the running instance
+pub enum Message {
+    Command(Command),
+    Rpc(RpcCall),
+}
+
+/// A JSON-RPC message waiting for the window to answer it, since the searcher
+/// and config live on the window's thread
+pub struct RpcCall {
+    pub message: String,
+    reply: Sender<Option<String>>,
+}
+
+impl RpcCall {
+    /// Send the response back to the client; `None` for notifications
+    pub fn reply(self, response: Option<String>) {
+        let _ = self.reply.send(response);
+    }
+}
+
+/// Connection to the instance that already owns the socket
+pub struct IpcClient {
+    stream: backend::Stream,
+}
+
+impl IpcClient {
+    /// Connect to the running instance, or `None` if there isn't one
+    pub fn connect() -> Option<Self> {
+        backend::connect().ok().map(|stream| Self { stream })
+    }
+
+    /// Send a command and wait for the instance to accept it
+    pub fn send(mut self, command: &Command) -> Result<(), Box<dyn std::error::Error>> {
+        writeln!(self.stream, "{}", command)?;
+        self.stream.flush()?;
+
+        let mut reply = String::new();
+        BufReader::new(&mut self.stream).read_line(&mut reply)?;
+        match reply.trim() {
+            "ok" => Ok(()),
+            "" => Err("The running instance closed the connection".into()),
+            error => Err(error.into()),
+        }
+    }
+}
+
+type Waker = Arc<Mutex<Option<Box<dyn Fn() + Send>>>>;
+
+/// Makes this process the single instance: owns the socket (a named pipe on
+/// Windows) and queues the commands later invocations send to it. Lines that
+/// start with `{` or `[` are JSON-RPC messages instead, see [`Rpc`].
+pub struct IpcServer {
+    messages: Receiver<Message>,
+    waker: Waker,
+}
+
+impl IpcServer {
+    /// How long a JSON-RPC client waits for the window thread before it gets
+    /// an error instead of hanging
+    const RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Take ownership of the socket. Fails if another instance owns it; a socket
+    /// left behind by a crashed instance is replaced.
+    pub fn start() -> io::Result<Self> {
+        let (tx, messages) = mpsc::channel();
+        let waker: Waker = Arc::new(Mutex::new(None));
+
+        let connection_waker = Arc::clone(&waker);
+        backend::serve(move |stream| {
+            let tx = tx.clone();
+            let waker = Arc::clone(&connection_waker);
+            thread::spawn(move || Self::handle_connection(stream, &tx, &waker));
+        })?;
+
+        Ok(Self { messages, waker })
+    }
+
+    /// Called whenever a command arrives, so a window that's idle or hidden
+    /// still gets to handle it
+    pub fn set_waker(&self, wake: impl Fn() + Send + 'static) {
+        if let Ok(mut waker) = self.waker.lock() {
+            *waker = Some(Box::new(wake));
+        }
+    }
+
+    /// Messages received since the last call, oldest first
+    pub fn poll(&self) -> Vec<Message> {
+        self.messages.try_iter().collect()
+    }
+
+    /// Answer each line until the client hangs up, so scripts can keep one
+    /// connection open for many JSON-RPC calls
+    fn handle_connection(mut stream: backend::Stream, tx: &Sender<Message>, waker: &Waker) {
+        let reader = match stream.try_clone() {
+            Ok(reader) => BufReader::new(reader),
+            Err(e) => {
+                eprintln!("Failed to read IPC connection: {}", e);
+                return;
+            }
+        };
+
+        for line in reader.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    eprintln!("Failed to read IPC command: {}", e);
+                    return;
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let reply = Self::answer(&line, tx, waker);
+            if let Some(reply) = reply {
+                if let Err(e) = writeln!(stream, "{}", reply) {
+                    eprintln!("Failed to answer IPC command: {}", e);
+                    return;
+                }
+            }
+        }
+
+        if let Err(e) = backend::finish(&mut stream) {
+            eprintln!("Failed to close IPC connection: {}", e);
+        }
+    }
+
+    /// Hand one line to the window and wait for the reply, if it needs one
+    fn answer(line: &str, tx: &Sender<Message>, waker: &Waker) -> Option<String> {
+        let wake = || {
+            if let Some(wake) = waker.lock().ok().as_ref().and_then(|waker| waker.as_ref()) {
+                wake();
+            }
+        };
+
+        if line.trim_start().starts_with(['{', '[']) {
+            let (reply, response) = mpsc::channel();
+            let call = RpcCall {
+                message: line.to_string(),
+                reply,
+            };
+            if tx.send(Message::Rpc(call)).is_ok() {
+                wake();
+            }
+            let message = match response.recv_timeout(Self::RPC_TIMEOUT) {
+                Ok(response) => return response,
+                Err(RecvTimeoutError::Timeout) => "The launcher didn't answer in time",
+                Err(RecvTimeoutError::Disconnected) => "The launcher is shutting down",
+            };
+            // Answer with the request's id where there is one; notifications stay unanswered
+            let id = match serde_json::from_str::<Value>(line) {
+                Ok(Value::Object(request)) => request.get("id").cloned()?,
+                _ => Value::Null,
+            };
+            return Some(Rpc::error_response(id, Rpc::INTERNAL_ERROR, message).to_string());
+        }
+
+        match Command::parse(line) {
+            Some(command) => {
+                if tx.send(Message::Command(command)).is_ok() {
+                    wake();
+                }
+                Some("ok".to_string())
+            }
+            None => Some(format!("Unknown command {:?}", line.trim())),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    fs, io,
+    os::unix::{
+        fs::PermissionsExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::PathBuf,
+    thread,
+    time::Duration,
+};
+
+pub type Stream = UnixStream;
+
+/// `$XDG_RUNTIME_DIR/fade-launcher.sock`, or a per-user name in the temp dir
+fn socket_path() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join("fade-launcher.sock"),
+        None => {
+            let user = std::env::var("USER").unwrap_or_default();
+            std::env::temp_dir().join(format!("fade-launcher-{}.sock", user))
+        }
+    }
+}
+
+pub fn connect() -> io::Result<Stream> {
+    let stream = UnixStream::connect(socket_path())?;
+    // A hung instance shouldn't hang the hotkey that started us too
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    Ok(stream)
+}
+
+/// Bind the socket and hand every connection to `handle` on a background thread
+pub fn serve(mut handle: impl FnMut(Stream) + Send + 'static) -> io::Result<()> {
+    let path = socket_path();
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(e);
+            }
+            // Nobody listens, so an earlier instance crashed without cleaning up
+            fs::remove_file(&path)?;
+            UnixListener::bind(&path)?
+        }
+        Err(e) => return Err(e),
+    };
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => handle(stream),
+                Err(e) => eprintln!("IPC connection failed: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Done answering; the client sees end of file
+pub fn finish(stream: &mut Stream) -> io::Result<()> {
+    stream.shutdown(std::net::Shutdown::Write)
+}
@@ -0,0 +1,88 @@
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    os::windows::io::{FromRawHandle, RawHandle},
+    thread,
+};
+use windows::{
+    core::HSTRING,
+    Win32::{
+        Foundation::{ERROR_PIPE_CONNECTED, HANDLE},
+        Storage::FileSystem::{FILE_FLAGS_AND_ATTRIBUTES, FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX},
+        System::Pipes::{
+            ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE,
+            PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+        },
+    },
+};
+
+pub type Stream = File;
+
+/// Named pipes are per machine, so the user name keeps sessions apart
+fn pipe_name() -> String {
+    let user = std::env::var("USERNAME").unwrap_or_default();
+    format!(r"\\.\pipe\fade-launcher-{}", user)
+}
+
+pub fn connect() -> io::Result<Stream> {
+    OpenOptions::new().read(true).write(true).open(pipe_name())
+}
+
+/// Create the pipe and hand every connection to `handle` on a background thread
+pub fn serve(mut handle: impl FnMut(Stream) + Send + 'static) -> io::Result<()> {
+    let name = pipe_name();
+    // The first instance flag fails if another process already owns the name
+    let mut pipe = create_pipe(&name, FILE_FLAG_FIRST_PIPE_INSTANCE)?;
+
+    thread::spawn(move || loop {
+        let connected = unsafe { ConnectNamedPipe(pipe, None) };
+        // The client may connect between creating the pipe and waiting for it
+        let connected = match connected {
+            Ok(()) => true,
+            Err(e) => e.code() == ERROR_PIPE_CONNECTED.to_hresult(),
+        };
+        let stream = unsafe { File::from_raw_handle(pipe.0 as RawHandle) };
+
+        // Each client gets its own pipe instance, so open the next one first
+        let next = create_pipe(&name, FILE_FLAGS_AND_ATTRIBUTES(0));
+        if connected {
+            handle(stream);
+        }
+        match next {
+            Ok(next) => pipe = next,
+            Err(e) => {
+                eprintln!("Failed to create IPC pipe: {}", e);
+                return;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn create_pipe(name: &str, flags: FILE_FLAGS_AND_ATTRIBUTES) -> io::Result<HANDLE> {
+    let pipe = unsafe {
+        CreateNamedPipeW(
+            &HSTRING::from(name),
+            PIPE_ACCESS_DUPLEX | flags,
+            PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+            PIPE_UNLIMITED_INSTANCES,
+            4096,
+            4096,
+            0,
+            None,
+        )
+    };
+
+    if pipe.is_invalid() {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(pipe)
+    }
+}
+
+/// Done answering. Waits for the client to read the reply, since closing the
+/// pipe would throw it away.
+pub fn finish(stream: &mut Stream) -> io::Result<()> {
+    stream.sync_all()
+}
@@ -0,0 +1,35 @@
+//! Fade Launcher as a library: application indexing, search and ranking,
+//! launching, the config file and the instance IPC/JSON-RPC API. None of it
+//! needs egui; the launcher window and dmenu picker are behind the `gui` feature.
+
+pub mod app_info;
+pub mod search;
+pub mod fuzzy;
+pub mod history;
+pub mod index_cache;
+pub mod scanner;
+pub mod watcher;
+pub mod desktop_entry;
+pub mod platform;
+pub mod shell_link;
+pub mod pe_version;
+pub mod filter_rules;
+pub mod hotkey;
+pub mod config;
+pub mod config_format;
+pub mod config_watcher;
+mod settings;
+pub mod cli;
+pub mod ipc;
+pub mod rpc;
+
+#[cfg(feature = "gui")]
+pub mod app;
+#[cfg(feature = "gui")]
+pub mod ui;
+#[cfg(feature = "gui")]
+pub mod dmenu;
+#[cfg(feature = "gui")]
+pub mod theme;
+#[cfg(feature = "gui")]
+pub mod window;
@@ -1,60 +1,97 @@
-mod app;
-mod ui;
-mod search;
-mod config;
-mod theme;
-
-use eframe::egui;
+use fade_launcher::{app, cli, config, dmenu, ipc, window};
+use std::{cell::RefCell, rc::Rc, thread, time::Duration};
 
 fn main() -> Result<(), eframe::Error> {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
-    let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([800.0, 600.0])
-            .with_min_inner_size([600.0, 400.0])
-            .with_decorations(false) // Remove window decorations for sleek look
-            .with_transparent(true)
-            .with_resizable(true),
-        ..Default::default()
+    // Subcommands run without opening a window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "--dmenu") {
+        // Pick from lines on stdin instead of launching apps
+        std::process::exit(dmenu::DmenuPicker::run(&args[1..]));
+    }
+    // `--daemon` starts hidden and keeps running when the window is dismissed
+    let (daemon, command) = match args.as_slice() {
+        [flag] if flag == "--daemon" => (true, Some(ipc::Command::Hide)),
+        _ => (false, ipc::Command::from_args(&args)),
+    };
+    let Some(command) = command else {
+        std::process::exit(cli::Cli::run(&args));
+    };
+    
+    // Only one instance runs; later invocations hand it their command and exit
+    if forward(&command) {
+        return Ok(());
+    }
+    if !daemon && matches!(command, ipc::Command::Hide | ipc::Command::Quit) {
+        return Ok(()); // Nothing to hide or quit
+    }
+    let ipc_server = match ipc::IpcServer::start() {
+        Ok(server) => Some(server),
+        Err(e) => {
+            // Another launch, e.g. a double-pressed hotkey, may have taken the
+            // socket since we checked; it gets the command instead
+            for _ in 0..10 {
+                if forward(&command) {
+                    return Ok(());
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+            eprintln!("Failed to open the instance socket, other invocations will start their own window: {}", e);
+            None
+        }
     };
 
-    eframe::run_native(
-        "Fade Launcher",
-        options,
-        Box::new(|cc| {
-            // Setup custom fonts and style
-            setup_custom_fonts(&cc.egui_ctx);
-            setup_style(&cc.egui_ctx);
-            
-            Box::new(app::FadeLauncher::new(cc))
-        }),
-    )
-}
+    // Fall back to the defaults on a broken config file, and tell the user
+    let (config, config_error) = match config::Config::try_load() {
+        Ok(config) => (config, None),
+        Err(e) => {
+            eprintln!("Failed to load config, using defaults: {}", e);
+            (config::Config::default(), Some(format!("Config not loaded, using defaults: {}", e)))
+        }
+    };
 
-fn setup_custom_fonts(ctx: &egui::Context) {
-    let mut fonts = egui::FontDefinitions::default();
+    let launcher = Rc::new(RefCell::new(app::FadeLauncher::new(config, config_error, ipc_server, daemon)));
+    let mut next = Some(command);
+    if daemon && launcher.borrow().starts_in_background() {
+        next = launcher.borrow_mut().run_in_background();
+    }
     
-    // You can add custom fonts here later
-    // fonts.font_data.insert(
-    //     "custom_font".to_owned(),
-    //     egui::FontData::from_static(include_bytes!("../assets/font.ttf")),
-    // );
-    
-    ctx.set_fonts(fonts);
+    // A daemon that can't hide its window closes it, and opens a new one when
+    // it's asked to show again
+    while let Some(command) = next.take() {
+        let window = Rc::clone(&launcher);
+        let options = window::native_options(&launcher.borrow().config().ui);
+        eframe::run_native(
+            "Fade Launcher",
+            options,
+            Box::new(move |cc| {
+                // Setup custom fonts and style
+                window::setup_custom_fonts(&cc.egui_ctx);
+                window::setup_style(&cc.egui_ctx);
+                
+                window.borrow_mut().attach(&cc.egui_ctx);
+                window.borrow_mut().handle_command(&cc.egui_ctx, command);
+                Box::new(app::LauncherWindow(window))
+            }),
+        )?;
+        
+        if launcher.borrow().closed_to_background() {
+            next = launcher.borrow_mut().run_in_background();
+        }
+    }
+    Ok(())
 }
 
-fn setup_style(ctx: &egui::Context) {
-    let mut style = (*ctx.style()).clone();
-    
-    // Set the overall theme to dark to complement our gradient
-    ctx.set_visuals(egui::Visuals::dark());
-    
-    // Customize spacing and rounding
-    style.spacing.item_spacing = egui::vec2(12.0, 8.0);
-    style.spacing.button_padding = egui::vec2(16.0, 8.0);
-    style.visuals.window_rounding = egui::Rounding::same(15.0);
-    style.visuals.menu_rounding = egui::Rounding::same(10.0);
-    
-    ctx.set_style(style);
-}
\ No newline at end of file
+/// Hand `command` to the running instance. Returns false if there is none; a
+/// running instance that rejects the command ends this process.
+fn forward(command: &ipc::Command) -> bool {
+    let Some(client) = ipc::IpcClient::connect() else {
+        return false;
+    };
+    if let Err(e) = client.send(command) {
+        eprintln!("Failed to reach the running instance: {}", e);
+        std::process::exit(1);
+    }
+    true
+}
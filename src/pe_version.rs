@@ -0,0 +1,403 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+/// Version metadata read from the VS_VERSIONINFO resource of a PE executable
+/// Parsing only looks at bytes, so it works for any `.exe` on any platform,
+/// e.g. programs installed in a Wine prefix.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub file_description: Option<String>,
+    pub product_name: Option<String>,
+    pub company_name: Option<String>,
+    pub file_version: Option<String>,
+}
+
+// Resource type ID of version information
+const RT_VERSION: u32 = 16;
+
+// Signature of VS_FIXEDFILEINFO
+const FIXED_FILE_INFO_SIGNATURE: u32 = 0xFEEF_04BD;
+
+// Bytes read up front for the DOS, PE and section headers
+const HEADER_READ_SIZE: usize = 4096;
+
+// VS_VERSIONINFO starts with a 16-bit length, so version data is never larger
+const MAX_VERSION_DATA_SIZE: usize = u16::MAX as usize;
+
+impl VersionInfo {
+    /// Read version information from the PE file at `path`, reading only the
+    /// headers, the resource directories leading to RT_VERSION and its data
+    pub fn load(path: &Path) -> Option<Self> {
+        Self::read_from(File::open(path).ok()?)
+    }
+
+    fn read_from<R: Read + Seek>(mut source: R) -> Option<Self> {
+        let mut headers = Vec::with_capacity(HEADER_READ_SIZE);
+        (&mut source).take(HEADER_READ_SIZE as u64).read_to_end(&mut headers).ok()?;
+
+        let layout = PeLayout::parse(&headers)?;
+        let version_data = ResourceReader { source, layout }.version_data()?;
+
+        Self::parse_version_info(&version_data)
+    }
+
+    /// Best one-line description: FileDescription, falling back to ProductName
+    pub fn description(&self) -> Option<String> {
+        self.file_description.clone().or_else(|| self.product_name.clone())
+    }
+
+    fn parse_version_info(data: &[u8]) -> Option<Self> {
+        let root = Block::parse(data, 0)?;
+        if root.key != "VS_VERSION_INFO" {
+            return None;
+        }
+
+        let mut info = VersionInfo::default();
+        let mut fixed_version = None;
+
+        let fixed = data.get(root.value_offset..root.value_offset + root.value_length)?;
+        if root.value_length >= 52 && read_u32(fixed, 0) == Some(FIXED_FILE_INFO_SIGNATURE) {
+            let ms = read_u32(fixed, 8)?;
+            let ls = read_u32(fixed, 12)?;
+            fixed_version = Some(format!("{}.{}.{}.{}", ms >> 16, ms & 0xFFFF, ls >> 16, ls & 0xFFFF));
+        }
+
+        // Prefer US English string tables, otherwise take the first one
+        let tables: Vec<Block> = root
+            .children(data)
+            .into_iter()
+            .filter(|child| child.key == "StringFileInfo")
+            .flat_map(|string_file_info| string_file_info.children(data))
+            .collect();
+
+        let table = tables
+            .iter()
+            .find(|table| table.key.to_lowercase().starts_with("0409"))
+            .or_else(|| tables.first());
+
+        if let Some(table) = table {
+            for string in table.children(data) {
+                let value = string.string_value(data).filter(|v| !v.trim().is_empty());
+                match string.key.as_str() {
+                    "FileDescription" => info.file_description = value,
+                    "ProductName" => info.product_name = value,
+                    "CompanyName" => info.company_name = value,
+                    "FileVersion" => info.file_version = value,
+                    _ => {}
+                }
+            }
+        }
+
+        if info.file_version.is_none() {
+            info.file_version = fixed_version;
+        }
+
+        if info == VersionInfo::default() {
+            None
+        } else {
+            Some(info)
+        }
+    }
+}
+
+/// Headers needed to locate the resource section
+struct PeLayout {
+    resource_rva: u32,
+    sections: Vec<Section>,
+}
+
+struct Section {
+    virtual_address: u32,
+    virtual_size: u32,
+    raw_size: u32,
+    raw_offset: u32,
+}
+
+impl PeLayout {
+    fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(0..2)? != b"MZ" {
+            return None;
+        }
+
+        let pe_offset = read_u32(data, 0x3C)? as usize;
+        if data.get(pe_offset..pe_offset + 4)? != b"PE\0\0" {
+            return None;
+        }
+
+        let coff = pe_offset + 4;
+        let section_count = read_u16(data, coff + 2)? as usize;
+        let optional_header_size = read_u16(data, coff + 16)? as usize;
+        let optional = coff + 20;
+
+        // PE32 and PE32+ differ in where the data directories start
+        let (rva_count_offset, directories_offset) = match read_u16(data, optional)? {
+            0x10B => (92, 96),
+            0x20B => (108, 112),
+            _ => return None,
+        };
+
+        let directory_count = read_u32(data, optional + rva_count_offset)?;
+        if directory_count <= 2 {
+            return None;
+        }
+
+        // Data directory 2 is the resource table
+        let resource_rva = read_u32(data, optional + directories_offset + 2 * 8)?;
+        if resource_rva == 0 {
+            return None;
+        }
+
+        let section_table = optional + optional_header_size;
+        let sections = (0..section_count)
+            .map(|index| {
+                let header = section_table + index * 40;
+                Some(Section {
+                    virtual_size: read_u32(data, header + 8)?,
+                    virtual_address: read_u32(data, header + 12)?,
+                    raw_size: read_u32(data, header + 16)?,
+                    raw_offset: read_u32(data, header + 20)?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self { resource_rva, sections })
+    }
+
+    fn section_for(&self, rva: u32) -> Option<&Section> {
+        self.sections.iter().find(|section| {
+            let size = section.virtual_size.max(section.raw_size);
+            rva >= section.virtual_address && rva < section.virtual_address.saturating_add(size)
+        })
+    }
+}
+
+/// Reads the resource tree straight from the file by RVA, one directory at a time
+struct ResourceReader<R> {
+    source: R,
+    layout: PeLayout,
+}
+
+impl<R: Read + Seek> ResourceReader<R> {
+    /// The VS_VERSIONINFO bytes. Type -> name -> language; the first name and
+    /// language are good enough.
+    fn version_data(&mut self) -> Option<Vec<u8>> {
+        let names = self.find_directory_entry(0, Some(RT_VERSION))?;
+        let languages = self.find_directory_entry(names, None)?;
+        let data_entry = self.find_directory_entry(languages, None)?;
+
+        let entry = self.read_resource(data_entry, 8)?;
+        let data_rva = read_u32(&entry, 0)?;
+        let data_size = read_u32(&entry, 4)? as usize;
+        if data_size > MAX_VERSION_DATA_SIZE {
+            return None;
+        }
+
+        self.read(data_rva, data_size)
+    }
+
+    /// Offset of the entry with `id` (or the first entry) in the resource directory
+    /// at `directory`. Offsets in the tree are relative to the resource root.
+    fn find_directory_entry(&mut self, directory: u32, id: Option<u32>) -> Option<u32> {
+        let header = self.read_resource(directory, 16)?;
+        let named = read_u16(&header, 12)? as usize;
+        let ids = read_u16(&header, 14)? as usize;
+        let entries = self.read_resource(directory.checked_add(16)?, (named + ids) * 8)?;
+
+        entries.chunks_exact(8).find_map(|entry| {
+            let name = read_u32(entry, 0)?;
+            let offset = read_u32(entry, 4)?;
+
+            // Named entries have the high bit set and never match a numeric ID
+            let matches = match id {
+                Some(id) => name & 0x8000_0000 == 0 && name == id,
+                None => true,
+            };
+            matches.then_some(offset & 0x7FFF_FFFF)
+        })
+    }
+
+    /// `len` bytes at `offset` from the resource root
+    fn read_resource(&mut self, offset: u32, len: usize) -> Option<Vec<u8>> {
+        self.read(self.layout.resource_rva.checked_add(offset)?, len)
+    }
+
+    /// `len` bytes at `rva`, which must lie in the raw data of one section
+    fn read(&mut self, rva: u32, len: usize) -> Option<Vec<u8>> {
+        let section = self.layout.section_for(rva)?;
+        let offset = (rva - section.virtual_address) as usize;
+        if offset.checked_add(len)? > section.raw_size as usize {
+            return None;
+        }
+
+        let mut data = vec![0; len];
+        self.source
+            .seek(SeekFrom::Start(section.raw_offset as u64 + offset as u64))
+            .ok()?;
+        self.source.read_exact(&mut data).ok()?;
+        Some(data)
+    }
+}
+
+/// One node of the VS_VERSIONINFO tree: wLength, wValueLength, wType, szKey,
+/// padding, value, padding, children. Fields are aligned to 32 bits.
+struct Block {
+    key: String,
+    end: usize,
+    value_offset: usize,
+    value_length: usize, // In bytes
+    children_offset: usize,
+}
+
+impl Block {
+    fn parse(data: &[u8], start: usize) -> Option<Self> {
+        let length = read_u16(data, start)? as usize;
+        let value_length = read_u16(data, start + 2)? as usize;
+        let is_text = read_u16(data, start + 4)? == 1;
+        let end = start + length;
+        if length < 6 || end > data.len() {
+            return None;
+        }
+
+        let (key, key_end) = read_utf16_z(&data[..end], start + 6)?;
+        let value_offset = align4(key_end);
+
+        // Text values count 16-bit words, binary values count bytes
+        let value_length = if is_text { value_length * 2 } else { value_length };
+        let children_offset = align4(value_offset + value_length).min(end);
+
+        Some(Self {
+            key,
+            end,
+            value_offset,
+            value_length,
+            children_offset,
+        })
+    }
+
+    fn children(&self, data: &[u8]) -> Vec<Block> {
+        let mut children = Vec::new();
+        let mut offset = self.children_offset;
+
+        while offset + 6 <= self.end {
+            match Block::parse(&data[..self.end], offset) {
+                Some(child) if child.end > offset => {
+                    offset = align4(child.end);
+                    children.push(child);
+                }
+                _ => break,
+            }
+        }
+
+        children
+    }
+
+    fn string_value(&self, data: &[u8]) -> Option<String> {
+        if self.value_length == 0 || self.value_offset >= self.end {
+            return None;
+        }
+
+        read_utf16_z(&data[..self.end], self.value_offset).map(|(value, _)| value)
+    }
+}
+
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// NUL-terminated UTF-16LE string at `offset`, returning it with the offset just
+/// past the terminator (or the end of `data` if unterminated)
+fn read_utf16_z(data: &[u8], offset: usize) -> Option<(String, usize)> {
+    let bytes = data.get(offset..)?;
+    let mut units = Vec::new();
+    let mut end = data.len();
+
+    for (index, chunk) in bytes.chunks_exact(2).enumerate() {
+        let unit = u16::from_le_bytes([chunk[0], chunk[1]]);
+        if unit == 0 {
+            end = offset + index * 2 + 2;
+            break;
+        }
+        units.push(unit);
+    }
+
+    Some((String::from_utf16_lossy(&units), end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    // A PE32 image with one .rsrc section holding VS_VERSIONINFO: a German string
+    // table, then a US English one, and the version only in VS_FIXEDFILEINFO
+    const VERSION: &[u8] = include_bytes!("../tests/fixtures/version.exe");
+    // A PE32 image with a .text section and an empty resource directory entry
+    const NO_RESOURCES: &[u8] = include_bytes!("../tests/fixtures/no_resources.exe");
+
+    // SizeOfRawData of the first section header in both fixtures
+    const RAW_SIZE_OFFSET: usize = 0x40 + 4 + 20 + 224 + 16;
+
+    #[test]
+    fn reads_version_strings() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/version.exe");
+        let info = VersionInfo::load(&path).unwrap();
+
+        assert_eq!(info.file_description.as_deref(), Some("Fade Test Tool"));
+        assert_eq!(info.product_name.as_deref(), Some("Fade Launcher"));
+        assert_eq!(info.company_name.as_deref(), Some("Fade Project"));
+        assert_eq!(info.file_version.as_deref(), Some("1.2.3.4"));
+        assert_eq!(info.description().as_deref(), Some("Fade Test Tool"));
+    }
+
+    #[test]
+    fn reads_only_the_version_resource() {
+        // A resource section claiming far more than the file holds is fine, as
+        // long as the version data itself is there
+        let mut data = VERSION.to_vec();
+        data[RAW_SIZE_OFFSET..RAW_SIZE_OFFSET + 4].copy_from_slice(&0x7000_0000u32.to_le_bytes());
+
+        let info = VersionInfo::read_from(Cursor::new(data)).unwrap();
+        assert_eq!(info.product_name.as_deref(), Some("Fade Launcher"));
+    }
+
+    #[test]
+    fn no_resources_returns_none() {
+        assert!(VersionInfo::read_from(Cursor::new(NO_RESOURCES)).is_none());
+    }
+
+    #[test]
+    fn truncated_pe_returns_none() {
+        for len in 0..VERSION.len() {
+            assert!(
+                VersionInfo::read_from(Cursor::new(&VERSION[..len])).is_none(),
+                "parsed {} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn corrupted_pe_does_not_panic() {
+        for position in 0..VERSION.len() {
+            for value in [0x00, 0x7F, 0xFF] {
+                let mut data = VERSION.to_vec();
+                data[position] = value;
+                let _ = VersionInfo::read_from(Cursor::new(data));
+            }
+        }
+    }
+}
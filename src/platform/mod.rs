@@ -0,0 +1,66 @@
+use crate::config::ScanRoot;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[cfg(windows)]
+mod windows;
+#[cfg(unix)]
+mod unix;
+
+/// Operating-system specific parts of application discovery and launching
+/// The implementation is chosen at compile time; use [`current`] to get it.
+pub trait Platform: Send + Sync {
+    /// Directories scanned for applications when none are configured
+    fn default_scan_roots(&self) -> Vec<ScanRoot>;
+
+    /// File extensions (with leading dot) indexed when none are configured
+    fn default_file_extensions(&self) -> Vec<String>;
+
+    /// Whether the file at `path` should be indexed as something launchable
+    fn is_executable(&self, path: &Path, file_extensions: &[String]) -> bool;
+
+    /// Start the application at `path` without waiting for it
+    fn launch(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// The platform backend for the OS this binary was built for
+#[cfg(windows)]
+pub fn current() -> &'static dyn Platform {
+    &self::windows::WindowsPlatform
+}
+
+/// The platform backend for the OS this binary was built for
+#[cfg(unix)]
+pub fn current() -> &'static dyn Platform {
+    &self::unix::UnixPlatform
+}
+
+/// Absolute form of `path` with symlinks resolved, or `None` if it doesn't exist.
+/// The `\\?\` prefix Windows puts on canonical paths is dropped for local drives.
+pub fn canonical_path(path: &Path) -> Option<PathBuf> {
+    let canonical = fs::canonicalize(path).ok()?;
+
+    match canonical.to_str().and_then(|text| text.strip_prefix(r"\\?\")) {
+        Some(local) if !local.starts_with("UNC\\") => Some(PathBuf::from(local)),
+        _ => Some(canonical),
+    }
+}
+
+/// Lowercased extension of `path` with a leading dot, e.g. `.exe`
+fn dotted_extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy().to_lowercase()))
+}
+
+/// Directories listed in the `PATH` environment variable, split the platform's way
+fn path_env_dirs() -> Vec<PathBuf> {
+    std::env::var_os("PATH")
+        .map(|path_env| {
+            std::env::split_paths(&path_env)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,80 @@
+use super::Platform;
+use crate::{config::ScanRoot, desktop_entry::DesktopEntry};
+use std::{
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    process::Command,
+};
+
+/// Linux (and other XDG Unix) backend: freedesktop application entries plus
+/// anything with the exec bit set on PATH
+pub struct UnixPlatform;
+
+impl Platform for UnixPlatform {
+    fn default_scan_roots(&self) -> Vec<ScanRoot> {
+        // Freedesktop application entries come first, so they stand in for the
+        // binaries they run and higher-precedence entries override lower ones
+        let mut roots: Vec<ScanRoot> = DesktopEntry::application_dirs()
+            .into_iter()
+            .map(ScanRoot::new)
+            .collect();
+        
+        // User's own scripts and binaries
+        if let Some(home) = dirs::home_dir() {
+            roots.push(ScanRoot::shallow(home.join(".local").join("bin")));
+        }
+        
+        // System PATH directories
+        for dir in super::path_env_dirs() {
+            if !roots.iter().any(|root| root.path == dir) {
+                roots.push(ScanRoot::shallow(dir));
+            }
+        }
+        
+        roots
+    }
+    
+    fn default_file_extensions(&self) -> Vec<String> {
+        vec![".desktop".to_string()]
+    }
+    
+    fn is_executable(&self, path: &Path, file_extensions: &[String]) -> bool {
+        let Ok(metadata) = path.metadata() else {
+            return false;
+        };
+        
+        if !metadata.is_file() {
+            return false;
+        }
+        
+        // Configured extensions always count; otherwise any user/group/other exec bit does
+        super::dotted_extension(path).is_some_and(|ext| file_extensions.contains(&ext))
+            || metadata.permissions().mode() & 0o111 != 0
+    }
+    
+    fn launch(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if super::dotted_extension(path).as_deref() == Some(".desktop") {
+            // For desktop entries, run the expanded Exec line
+            let entry = DesktopEntry::load(path).ok_or("Unreadable desktop entry")?;
+            let args = entry.command_line(path).ok_or("Desktop entry has no Exec key")?;
+            
+            let mut command = Command::new(&args[0]);
+            command.args(&args[1..]);
+            if let Some(dir) = &entry.working_dir {
+                command.current_dir(dir);
+            }
+            command.spawn()?;
+        } else if self.is_executable(path, &[]) {
+            // For executables, launch directly
+            Command::new(path)
+                .spawn()?;
+        } else {
+            // Anything else goes to the desktop's default handler
+            Command::new("xdg-open")
+                .arg(path)
+                .spawn()?;
+        }
+        
+        Ok(())
+    }
+}
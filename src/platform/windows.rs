@@ -0,0 +1,99 @@
+use super::Platform;
+use crate::{config::ScanRoot, shell_link::ShellLink};
+use std::{
+    os::windows::process::CommandExt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Windows backend: Program Files, Start Menu and PATH, matched by extension
+pub struct WindowsPlatform;
+
+impl Platform for WindowsPlatform {
+    fn default_scan_roots(&self) -> Vec<ScanRoot> {
+        let mut roots = Vec::new();
+        
+        // Common Windows application directories
+        if let Ok(program_files) = std::env::var("ProgramFiles") {
+            roots.push(ScanRoot::new(PathBuf::from(program_files)));
+        }
+        
+        if let Ok(program_files_x86) = std::env::var("ProgramFiles(x86)") {
+            roots.push(ScanRoot::new(PathBuf::from(program_files_x86)));
+        }
+        
+        // Windows Apps directory
+        if let Some(local_app_data) = dirs::data_local_dir() {
+            roots.push(ScanRoot::shallow(local_app_data.join("Microsoft").join("WindowsApps")));
+        }
+        
+        // Start Menu shortcuts
+        if let Some(start_menu) = dirs::data_dir() {
+            roots.push(ScanRoot::new(start_menu.join("Microsoft").join("Windows").join("Start Menu").join("Programs")));
+        }
+        
+        // Common shortcuts location
+        if let Ok(program_data) = std::env::var("ProgramData") {
+            roots.push(ScanRoot::new(
+                PathBuf::from(program_data).join("Microsoft").join("Windows").join("Start Menu").join("Programs"),
+            ));
+        }
+        
+        // User's desktop
+        if let Some(desktop) = dirs::desktop_dir() {
+            roots.push(ScanRoot::shallow(desktop));
+        }
+        
+        // System PATH directories
+        roots.extend(super::path_env_dirs().into_iter().map(ScanRoot::shallow));
+        
+        roots
+    }
+    
+    fn default_file_extensions(&self) -> Vec<String> {
+        [".exe", ".msi", ".bat", ".cmd", ".com", ".lnk"]
+            .iter()
+            .map(|ext| ext.to_string())
+            .collect()
+    }
+    
+    fn is_executable(&self, path: &Path, file_extensions: &[String]) -> bool {
+        path.is_file()
+            && super::dotted_extension(path).is_some_and(|ext| file_extensions.contains(&ext))
+    }
+    
+    fn launch(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if super::dotted_extension(path).as_deref() == Some(".lnk") {
+            // Run the resolved target directly so the link's arguments and working directory apply
+            let link = ShellLink::load(path);
+            if let Some((link, target)) = link.as_ref().and_then(|link| {
+                let target = link.target_path.as_deref().filter(|t| Path::new(t).is_file())?;
+                Some((link, target))
+            }) {
+                let mut command = Command::new(target);
+                if let Some(arguments) = &link.arguments {
+                    command.raw_arg(arguments);
+                }
+                if let Some(dir) = link.working_dir.as_deref().filter(|d| Path::new(d).is_dir()) {
+                    command.current_dir(dir);
+                }
+                command.spawn()?;
+                return Ok(());
+            }
+            
+            // Otherwise (e.g. shell namespace targets) use Windows shell to open
+            Command::new("cmd")
+                .arg("/C")
+                .arg("start")
+                .arg("")
+                .arg(path)
+                .spawn()?;
+        } else {
+            // For executables, launch directly
+            Command::new(path)
+                .spawn()?;
+        }
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,442 @@
+use crate::{app_info::AppInfo, search::IndexStats};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// JSON-RPC 2.0 API served on the instance socket, one message per line.
+/// The methods are listed in the README; [`RpcTarget`] is what they act on.
+pub struct Rpc;
+
+/// What the RPC methods act on. The launcher window implements it over the
+/// same searcher, history and config it uses itself.
+pub trait RpcTarget {
+    /// Best matches for `query`, at most `limit` (default: `search.max_results`)
+    fn search(&self, query: &str, limit: Option<usize>) -> Vec<AppInfo>;
+
+    /// Launch an app by name or path and record it as recently used
+    fn launch(&mut self, name_or_path: &str) -> Result<AppInfo, String>;
+
+    fn recent(&self, limit: Option<usize>) -> Vec<AppInfo>;
+
+    /// Load the config file again, as if it had just been edited
+    fn reload_config(&mut self) -> Result<(), String>;
+
+    fn rescan(&mut self);
+
+    fn get_setting(&self, path: &str) -> Result<Value, String>;
+
+    /// Change a setting in the running instance, and in the config file if `save`
+    fn set_setting(&mut self, path: &str, value: Value, save: bool) -> Result<(), String>;
+
+    fn index_stats(&self) -> IndexStats;
+}
+
+/// An app as returned by the API and printed by `--json`
+#[derive(Serialize)]
+pub struct AppOutput<'a> {
+    pub name: &'a str,
+    pub path: &'a str,
+    pub target: Option<&'a str>,
+    pub description: Option<&'a str>,
+    pub keywords: &'a [String],
+    pub score: f32,
+}
+
+impl<'a> From<&'a AppInfo> for AppOutput<'a> {
+    fn from(app: &'a AppInfo) -> Self {
+        Self {
+            name: &app.name,
+            path: &app.path,
+            target: app.target.as_deref(),
+            description: app.description.as_deref(),
+            keywords: &app.keywords,
+            score: app.score,
+        }
+    }
+}
+
+/// Error object of a failed call
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn invalid_params(message: String) -> Self {
+        Self { code: Rpc::INVALID_PARAMS, message }
+    }
+
+    /// The method ran but didn't succeed, e.g. an unknown setting
+    fn failed(message: String) -> Self {
+        Self { code: Rpc::FAILED, message }
+    }
+}
+
+/// Parameters given by name (an object) or by position (an array)
+struct Params(Value);
+
+impl Params {
+    fn get(&self, name: &str, position: usize) -> Option<&Value> {
+        match &self.0 {
+            Value::Object(params) => params.get(name),
+            Value::Array(params) => params.get(position),
+            _ => None,
+        }
+    }
+
+    fn string(&self, name: &str, position: usize) -> Result<&str, RpcError> {
+        match self.get(name, position) {
+            Some(Value::String(text)) => Ok(text),
+            Some(_) => Err(RpcError::invalid_params(format!("{:?} must be a string", name))),
+            None => Err(RpcError::invalid_params(format!("Missing parameter {:?}", name))),
+        }
+    }
+
+    fn limit(&self, position: usize) -> Result<Option<usize>, RpcError> {
+        match self.get("limit", position) {
+            None | Some(Value::Null) => Ok(None),
+            Some(limit) => match limit.as_u64() {
+                Some(limit) if limit > 0 => Ok(Some(limit as usize)),
+                _ => Err(RpcError::invalid_params("\"limit\" must be a positive integer".to_string())),
+            },
+        }
+    }
+
+    fn flag(&self, name: &str, position: usize) -> Result<bool, RpcError> {
+        match self.get(name, position) {
+            None | Some(Value::Null) => Ok(false),
+            Some(Value::Bool(flag)) => Ok(*flag),
+            Some(_) => Err(RpcError::invalid_params(format!("{:?} must be true or false", name))),
+        }
+    }
+}
+
+impl Rpc {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+    /// Application error: the call was valid but failed
+    pub const FAILED: i64 = -32000;
+
+    /// Answer one message: a request, a notification or a batch of them.
+    /// Returns `None` when there's nothing to answer, i.e. only notifications.
+    pub fn handle(message: &str, target: &mut dyn RpcTarget) -> Option<String> {
+        let response = match serde_json::from_str::<Value>(message) {
+            Err(e) => Some(Self::error_response(Value::Null, Self::PARSE_ERROR, &format!("Parse error: {}", e))),
+            Ok(Value::Array(batch)) if batch.is_empty() => {
+                Some(Self::error_response(Value::Null, Self::INVALID_REQUEST, "Empty batch"))
+            }
+            Ok(Value::Array(batch)) => {
+                let responses: Vec<Value> = batch
+                    .into_iter()
+                    .filter_map(|request| Self::handle_request(request, target))
+                    .collect();
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            Ok(request) => Self::handle_request(request, target),
+        };
+
+        response.map(|response| response.to_string())
+    }
+
+    /// The error response for `id`, for errors found before a request is handled
+    pub fn error_response(id: Value, code: i64, message: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "error": { "code": code, "message": message },
+            "id": id,
+        })
+    }
+
+    fn handle_request(request: Value, target: &mut dyn RpcTarget) -> Option<Value> {
+        let id = request.get("id").cloned();
+        let method = request.get("method").and_then(Value::as_str);
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let valid_id = matches!(id, None | Some(Value::Null | Value::Number(_) | Value::String(_)));
+        let valid = valid_id
+            && request.get("jsonrpc") == Some(&json!("2.0"))
+            && matches!(params, Value::Null | Value::Array(_) | Value::Object(_));
+        let Some(method) = method.filter(|_| valid) else {
+            // Answered even without an id, since the id may be what's wrong
+            let id = id.filter(|_| valid_id).unwrap_or(Value::Null);
+            return Some(Self::error_response(id, Self::INVALID_REQUEST, "Invalid request"));
+        };
+
+        let result = Self::call(method, &Params(params), target);
+
+        // Notifications run but get no response
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+            Err(e) => Self::error_response(id, e.code, &e.message),
+        })
+    }
+
+    fn call(method: &str, params: &Params, target: &mut dyn RpcTarget) -> Result<Value, RpcError> {
+        match method {
+            "search" => {
+                let query = params.string("query", 0)?;
+                Ok(Self::apps(&target.search(query, params.limit(1)?)))
+            }
+            "launch" => {
+                let app = target.launch(params.string("name", 0)?).map_err(RpcError::failed)?;
+                Ok(json!(AppOutput::from(&app)))
+            }
+            "recent" => Ok(Self::apps(&target.recent(params.limit(0)?))),
+            "reload_config" => target.reload_config().map(|()| Value::Null).map_err(RpcError::failed),
+            "rescan" => {
+                target.rescan();
+                Ok(Value::Null)
+            }
+            "get_setting" => target.get_setting(params.string("path", 0)?).map_err(RpcError::failed),
+            "set_setting" => {
+                let path = params.string("path", 0)?;
+                let value = params
+                    .get("value", 1)
+                    .cloned()
+                    .ok_or_else(|| RpcError::invalid_params("Missing parameter \"value\"".to_string()))?;
+                let save = params.flag("save", 2)?;
+
+                target.set_setting(path, value, save).map(|()| Value::Null).map_err(RpcError::failed)
+            }
+            "index_stats" => serde_json::to_value(target.index_stats())
+                .map_err(|e| RpcError { code: Self::INTERNAL_ERROR, message: e.to_string() }),
+            _ => Err(RpcError {
+                code: Self::METHOD_NOT_FOUND,
+                message: format!("Method not found: {}", method),
+            }),
+        }
+    }
+
+    fn apps(apps: &[AppInfo]) -> Value {
+        json!(apps.iter().map(AppOutput::from).collect::<Vec<_>>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stands in for the launcher: a fixed list of apps and a flat settings map
+    #[derive(Default)]
+    struct FakeLauncher {
+        launched: Vec<String>,
+        settings: serde_json::Map<String, Value>,
+        saved: bool,
+        rescans: usize,
+    }
+
+    fn app(name: &str) -> AppInfo {
+        AppInfo {
+            name: name.to_string(),
+            path: format!("/usr/bin/{}", name.to_lowercase()),
+            target: None,
+            sources: Vec::new(),
+            path_hint: None,
+            icon_path: None,
+            description: None,
+            hotkey: None,
+            keywords: Vec::new(),
+            version_info: None,
+            score: 1.0,
+            name_matches: Vec::new(),
+            path_matches: Vec::new(),
+        }
+    }
+
+    impl RpcTarget for FakeLauncher {
+        fn search(&self, query: &str, limit: Option<usize>) -> Vec<AppInfo> {
+            ["Firefox", "Files", "Terminal"]
+                .into_iter()
+                .filter(|name| name.to_lowercase().contains(&query.to_lowercase()))
+                .take(limit.unwrap_or(10))
+                .map(app)
+                .collect()
+        }
+
+        fn launch(&mut self, name_or_path: &str) -> Result<AppInfo, String> {
+            let app = self.search(name_or_path, Some(1)).pop().ok_or(format!("No application matches {:?}", name_or_path))?;
+            self.launched.push(app.name.clone());
+            Ok(app)
+        }
+
+        fn recent(&self, limit: Option<usize>) -> Vec<AppInfo> {
+            self.launched.iter().rev().take(limit.unwrap_or(10)).map(|name| app(name)).collect()
+        }
+
+        fn reload_config(&mut self) -> Result<(), String> {
+            Err("line 3, column 1: expected value".to_string())
+        }
+
+        fn rescan(&mut self) {
+            self.rescans += 1;
+        }
+
+        fn get_setting(&self, path: &str) -> Result<Value, String> {
+            self.settings.get(path).cloned().ok_or(format!("Unknown setting {:?}", path))
+        }
+
+        fn set_setting(&mut self, path: &str, value: Value, save: bool) -> Result<(), String> {
+            self.settings.insert(path.to_string(), value);
+            self.saved |= save;
+            Ok(())
+        }
+
+        fn index_stats(&self) -> IndexStats {
+            IndexStats {
+                apps: 3,
+                files: 4,
+                scan_roots: 2,
+                missing_roots: 0,
+                scanning: false,
+            }
+        }
+    }
+
+    fn call(launcher: &mut FakeLauncher, message: &str) -> Option<Value> {
+        Rpc::handle(message, launcher).map(|response| serde_json::from_str(&response).unwrap())
+    }
+
+    fn error_code(response: &Value) -> i64 {
+        response["error"]["code"].as_i64().unwrap()
+    }
+
+    #[test]
+    fn search_with_named_and_positional_params() {
+        let mut launcher = FakeLauncher::default();
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"search","params":{"query":"fi"},"id":1}"#).unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"][0]["name"], "Firefox");
+        assert_eq!(response["result"][1]["path"], "/usr/bin/files");
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"search","params":["fi",1],"id":"a"}"#).unwrap();
+        assert_eq!(response["id"], "a");
+        assert_eq!(response["result"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn launch_then_recent() {
+        let mut launcher = FakeLauncher::default();
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"launch","params":{"name":"term"},"id":1}"#).unwrap();
+        assert_eq!(response["result"]["name"], "Terminal");
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"recent","id":2}"#).unwrap();
+        assert_eq!(response["result"][0]["name"], "Terminal");
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"launch","params":["nothing"],"id":3}"#).unwrap();
+        assert_eq!(error_code(&response), Rpc::FAILED);
+        assert!(response.get("result").is_none());
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let mut launcher = FakeLauncher::default();
+
+        let response = call(
+            &mut launcher,
+            r#"{"jsonrpc":"2.0","method":"set_setting","params":{"path":"search.max_results","value":5,"save":true},"id":1}"#,
+        )
+        .unwrap();
+        assert_eq!(response["result"], Value::Null);
+        assert!(launcher.saved);
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"get_setting","params":["search.max_results"],"id":2}"#).unwrap();
+        assert_eq!(response["result"], 5);
+
+        // Null is a value like any other, not a missing parameter
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"set_setting","params":["a.b",null],"id":3}"#).unwrap();
+        assert!(response.get("error").is_none());
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"set_setting","params":{"path":"a.b"},"id":4}"#).unwrap();
+        assert_eq!(error_code(&response), Rpc::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn reload_rescan_and_stats() {
+        let mut launcher = FakeLauncher::default();
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"reload_config","id":1}"#).unwrap();
+        assert_eq!(error_code(&response), Rpc::FAILED);
+        assert_eq!(response["error"]["message"], "line 3, column 1: expected value");
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"rescan","id":2}"#).unwrap();
+        assert_eq!(response["result"], Value::Null);
+        assert_eq!(launcher.rescans, 1);
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"index_stats","id":3}"#).unwrap();
+        assert_eq!(response["result"]["apps"], 3);
+        assert_eq!(response["result"]["scanning"], false);
+    }
+
+    #[test]
+    fn notifications_get_no_response() {
+        let mut launcher = FakeLauncher::default();
+
+        assert_eq!(call(&mut launcher, r#"{"jsonrpc":"2.0","method":"rescan"}"#), None);
+        assert_eq!(launcher.rescans, 1);
+
+        // Even failing ones
+        assert_eq!(call(&mut launcher, r#"{"jsonrpc":"2.0","method":"nope"}"#), None);
+    }
+
+    #[test]
+    fn protocol_errors() {
+        let mut launcher = FakeLauncher::default();
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"search""#).unwrap();
+        assert_eq!(error_code(&response), Rpc::PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+
+        let response = call(&mut launcher, r#"{"method":"search","params":["fi"],"id":1}"#).unwrap();
+        assert_eq!(error_code(&response), Rpc::INVALID_REQUEST);
+        assert_eq!(response["id"], 1);
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"search","params":"fi","id":1}"#).unwrap();
+        assert_eq!(error_code(&response), Rpc::INVALID_REQUEST);
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"search","params":{"query":"fi"},"id":{}}"#).unwrap();
+        assert_eq!(error_code(&response), Rpc::INVALID_REQUEST);
+        assert_eq!(response["id"], Value::Null);
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"open_pod_bay_doors","id":1}"#).unwrap();
+        assert_eq!(error_code(&response), Rpc::METHOD_NOT_FOUND);
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"search","params":{"query":7},"id":1}"#).unwrap();
+        assert_eq!(error_code(&response), Rpc::INVALID_PARAMS);
+
+        let response = call(&mut launcher, r#"{"jsonrpc":"2.0","method":"recent","params":{"limit":0},"id":1}"#).unwrap();
+        assert_eq!(error_code(&response), Rpc::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn batches() {
+        let mut launcher = FakeLauncher::default();
+
+        let response = call(
+            &mut launcher,
+            r#"[
+                {"jsonrpc":"2.0","method":"search","params":["term"],"id":1},
+                {"jsonrpc":"2.0","method":"rescan"},
+                {"jsonrpc":"2.0","method":"index_stats","id":2},
+                42
+            ]"#,
+        )
+        .unwrap();
+        let responses = response.as_array().unwrap();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["result"][0]["name"], "Terminal");
+        assert_eq!(responses[1]["id"], 2);
+        assert_eq!(error_code(&responses[2]), Rpc::INVALID_REQUEST);
+
+        // A batch of notifications has nothing to answer
+        assert_eq!(call(&mut launcher, r#"[{"jsonrpc":"2.0","method":"rescan"}]"#), None);
+
+        let response = call(&mut launcher, "[]").unwrap();
+        assert_eq!(error_code(&response), Rpc::INVALID_REQUEST);
+    }
+}
@@ -0,0 +1,469 @@
+use crate::{
+    app_info::AppInfo,
+    config::ScanRoot,
+    desktop_entry::DesktopEntry,
+    filter_rules::FilterRules,
+    index_cache::{IndexCache, Mtimes},
+    search::AppSearcher,
+};
+use std::{
+    collections::HashSet,
+    fmt,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+use walkdir::WalkDir;
+
+/// Progress of a scan, sent over the channel returned by [`IndexScan::start`]
+/// File and app counts are deltas since the previous event for the same root.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanEvent {
+    Started { roots: usize },
+    RootStarted { root: PathBuf },
+    FilesSeen { root: PathBuf, count: usize },
+    AppsFound { root: PathBuf, count: usize },
+    RootFinished { root: PathBuf },
+    Finished { apps: usize, cancelled: bool }, // `apps` is the merged index size
+}
+
+/// Running totals built from [`ScanEvent`]s, shown while the index is built
+#[derive(Debug, Clone, Default)]
+pub struct ScanProgress {
+    pub roots_total: usize,
+    pub roots_done: usize,
+    pub files_seen: usize,
+    pub apps_found: usize,
+    pub running: bool,
+}
+
+impl ScanProgress {
+    pub fn apply(&mut self, event: &ScanEvent) {
+        match event {
+            ScanEvent::Started { roots } => {
+                *self = Self {
+                    roots_total: *roots,
+                    running: true,
+                    ..Self::default()
+                };
+            }
+            ScanEvent::RootStarted { .. } => {}
+            ScanEvent::FilesSeen { count, .. } => self.files_seen += count,
+            ScanEvent::AppsFound { count, .. } => self.apps_found += count,
+            ScanEvent::RootFinished { .. } => self.roots_done += 1,
+            ScanEvent::Finished { apps, .. } => {
+                self.apps_found = *apps;
+                self.running = false;
+            }
+        }
+    }
+}
+
+impl fmt::Display for ScanProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Indexing {}/{} roots, {} apps",
+            self.roots_done, self.roots_total, self.apps_found
+        )
+    }
+}
+
+/// Counts the scans whose result isn't in the index yet. A finished scan replaces
+/// the whole index, so the watcher holds back its changes while one is running.
+#[derive(Clone, Default)]
+pub struct ScanTracker {
+    running: Arc<AtomicUsize>,
+}
+
+/// Marks a scan as running until dropped
+struct RunningScan(Arc<AtomicUsize>);
+
+impl ScanTracker {
+    pub fn is_scanning(&self) -> bool {
+        self.running.load(Ordering::SeqCst) > 0
+    }
+
+    fn begin(&self) -> RunningScan {
+        self.running.fetch_add(1, Ordering::SeqCst);
+        RunningScan(Arc::clone(&self.running))
+    }
+}
+
+impl Drop for RunningScan {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A background scan of every root, walking several roots at once
+/// When it completes the index and its cache are replaced; a cancelled scan
+/// leaves both untouched. Dropping the handle cancels the scan. Until the thread
+/// ends, its [`ScanTracker`] reports a scan as running.
+pub struct IndexScan {
+    cancelled: Arc<AtomicBool>,
+}
+
+/// What one worker found below one root
+#[derive(Default)]
+struct RootScan {
+    apps: Vec<(Option<String>, AppInfo)>, // Desktop file ID (if any) and entry
+    desktop_ids: Vec<String>, // Every desktop file ID seen, including hidden entries
+    mtimes: Mtimes,
+}
+
+impl IndexScan {
+    // Files walked between progress events for a root
+    const PROGRESS_INTERVAL: usize = 256;
+
+    /// Start scanning. With `cached`, the scan ends right away if no directory
+    /// changed since that cache was written.
+    pub fn start(
+        indexed_apps: Arc<Mutex<Vec<AppInfo>>>,
+        scan_roots: Vec<ScanRoot>,
+        file_extensions: Vec<String>,
+        filter_rules: Arc<FilterRules>,
+        cached: Option<IndexCache>,
+        tracker: &ScanTracker,
+    ) -> (Self, Receiver<ScanEvent>) {
+        let (tx, rx) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let cancel_flag = Arc::clone(&cancelled);
+        let running = tracker.begin();
+
+        thread::spawn(move || {
+            if let Some(cache) = cached.filter(|cache| cache.is_fresh()) {
+                eprintln!("Index cache is up to date, skipping scan");
+                let _ = tx.send(ScanEvent::Finished {
+                    apps: cache.apps.len(),
+                    cancelled: false,
+                });
+                return;
+            }
+
+            let _ = tx.send(ScanEvent::Started { roots: scan_roots.len() });
+
+            let Some((apps, mtimes)) = Self::scan(&scan_roots, &file_extensions, &filter_rules, &cancel_flag, &tx)
+            else {
+                eprintln!("Indexing cancelled");
+                let _ = tx.send(ScanEvent::Finished { apps: 0, cancelled: true });
+                return;
+            };
+
+            let count = apps.len();
+            let Ok(mut indexed) = indexed_apps.lock() else {
+                return;
+            };
+
+            // A restart may have cancelled this scan after the walk finished. The
+            // newer scan swaps and saves under the same lock, so checking here
+            // keeps a stale result from overwriting its index or cache.
+            if cancel_flag.load(Ordering::Relaxed) {
+                drop(indexed);
+                eprintln!("Indexing cancelled");
+                let _ = tx.send(ScanEvent::Finished { apps: 0, cancelled: true });
+                return;
+            }
+
+            let cache = IndexCache::new(&scan_roots, &file_extensions, &filter_rules, mtimes, apps.clone());
+            if let Err(e) = cache.save() {
+                eprintln!("Failed to save index cache: {}", e);
+            }
+            *indexed = apps;
+            drop(running);
+            drop(indexed);
+            eprintln!("App indexing completed. Found {} applications", count);
+
+            let _ = tx.send(ScanEvent::Finished { apps: count, cancelled: false });
+        });
+
+        (Self { cancelled }, rx)
+    }
+
+    /// Stop walking as soon as possible, keeping the current index
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Walks the roots on a pool of worker threads, then combines the results in
+    /// root order so precedence between roots doesn't depend on timing.
+    /// Returns `None` if cancelled.
+    fn scan(
+        scan_roots: &[ScanRoot],
+        file_extensions: &[String],
+        filter_rules: &FilterRules,
+        cancelled: &AtomicBool,
+        events: &Sender<ScanEvent>,
+    ) -> Option<(Vec<AppInfo>, Mtimes)> {
+        let workers = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4)
+            .min(scan_roots.len())
+            .max(1);
+        let next_root = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<RootScan>>> = Mutex::new((0..scan_roots.len()).map(|_| None).collect());
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    let events = events.clone();
+
+                    loop {
+                        let index = next_root.fetch_add(1, Ordering::Relaxed);
+                        let Some(root) = scan_roots.get(index) else {
+                            break;
+                        };
+                        if cancelled.load(Ordering::Relaxed) {
+                            break;
+                        }
+
+                        let _ = events.send(ScanEvent::RootStarted { root: root.path.clone() });
+                        let root_scan = Self::scan_root(root, file_extensions, filter_rules, cancelled, &events);
+                        let _ = events.send(ScanEvent::RootFinished { root: root.path.clone() });
+
+                        if let Ok(mut results) = results.lock() {
+                            results[index] = Some(root_scan);
+                        }
+                    }
+                });
+            }
+        });
+
+        if cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let mut apps = Vec::new();
+        let mut seen_desktop_ids = HashSet::new();
+        let mut mtimes = Mtimes::default();
+
+        for root_scan in results.into_inner().ok()?.into_iter().flatten() {
+            // A desktop file ID seen in an earlier root overrides this one, even if
+            // that entry was hidden
+            for (id, app_info) in root_scan.apps {
+                if id.is_none_or(|id| !seen_desktop_ids.contains(&id)) {
+                    apps.push(app_info);
+                }
+            }
+            seen_desktop_ids.extend(root_scan.desktop_ids);
+            mtimes.extend(root_scan.mtimes);
+        }
+
+        let mut apps = AppSearcher::merge_duplicates(apps);
+
+        // Sort by name for better organization
+        apps.sort_by_key(|a| a.name.to_lowercase());
+
+        Some((apps, mtimes))
+    }
+
+    fn scan_root(
+        root: &ScanRoot,
+        file_extensions: &[String],
+        filter_rules: &FilterRules,
+        cancelled: &AtomicBool,
+        events: &Sender<ScanEvent>,
+    ) -> RootScan {
+        let mut root_scan = RootScan::default();
+
+        if !root.path.exists() {
+            // Remember missing roots too, so the cache notices when they appear
+            root_scan.mtimes.directories.insert(root.path.clone(), 0);
+            return root_scan;
+        }
+
+        eprintln!("Scanning: {:?}", root.path);
+
+        let walker = WalkDir::new(&root.path)
+            .max_depth(root.max_depth)
+            .follow_links(root.follow_links)
+            .into_iter()
+            .filter_entry(|e| root.covers(e.path()))
+            .filter_map(|e| e.ok());
+
+        let (mut files, mut found) = (0, 0);
+        let report = |files: &mut usize, found: &mut usize| {
+            let root = root.path.clone();
+            let _ = events.send(ScanEvent::FilesSeen { root: root.clone(), count: *files });
+            let _ = events.send(ScanEvent::AppsFound { root, count: *found });
+            (*files, *found) = (0, 0);
+        };
+
+        for entry in walker {
+            if cancelled.load(Ordering::Relaxed) {
+                break;
+            }
+
+            let path = entry.path();
+
+            if entry.file_type().is_dir() {
+                root_scan.mtimes.directories.insert(path.to_path_buf(), IndexCache::mtime(path));
+                continue;
+            }
+
+            files += 1;
+
+            let desktop_id = DesktopEntry::desktop_file_id(&root.path, path);
+            if let Some(id) = &desktop_id {
+                root_scan.desktop_ids.push(id.clone());
+            }
+
+            let app_info = AppSearcher::index_entry(path, root.extensions(file_extensions), filter_rules);
+
+            // Hidden desktop entries too, since an edit can make them visible
+            if desktop_id.is_some() || app_info.is_some() {
+                root_scan.mtimes.files.insert(path.to_path_buf(), IndexCache::mtime(path));
+            }
+            if let Some(app_info) = app_info {
+                root_scan.apps.push((desktop_id, app_info));
+                found += 1;
+            }
+
+            if files >= Self::PROGRESS_INTERVAL {
+                report(&mut files, &mut found);
+            }
+        }
+
+        report(&mut files, &mut found);
+        root_scan
+    }
+}
+
+impl Drop for IndexScan {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    /// Fresh directory holding `count` files with the `.app` extension
+    fn root_with_apps(name: &str, count: usize) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fade-launcher-scan-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for i in 0..count {
+            let parent = if i % 2 == 0 { dir.clone() } else { dir.join("sub") };
+            fs::write(parent.join(format!("tool{}.app", i)), "").unwrap();
+        }
+        dir
+    }
+
+    /// Starts a scan of `dir` into `indexed`, without a cache
+    fn start_scan(dir: &Path, indexed: &Arc<Mutex<Vec<AppInfo>>>, tracker: &ScanTracker) -> (IndexScan, Receiver<ScanEvent>) {
+        IndexScan::start(
+            Arc::clone(indexed),
+            vec![ScanRoot::new(dir.to_path_buf())],
+            vec![".app".to_string()],
+            Arc::new(FilterRules::new(&[], &[])),
+            None,
+            tracker,
+        )
+    }
+
+    /// Events up to and including `Finished`
+    fn events_until_finished(events: &Receiver<ScanEvent>) -> Vec<ScanEvent> {
+        let mut received = Vec::new();
+        for event in events.iter() {
+            let finished = matches!(event, ScanEvent::Finished { .. });
+            received.push(event);
+            if finished {
+                break;
+            }
+        }
+        received
+    }
+
+    #[test]
+    fn reports_progress_and_fills_the_index() {
+        let dir = root_with_apps("progress", 5);
+        let indexed = Arc::new(Mutex::new(Vec::new()));
+        let tracker = ScanTracker::default();
+
+        let (_scan, events) = start_scan(&dir, &indexed, &tracker);
+        let events = events_until_finished(&events);
+
+        assert_eq!(events.first(), Some(&ScanEvent::Started { roots: 1 }));
+        assert_eq!(events[1], ScanEvent::RootStarted { root: dir.clone() });
+        assert!(events.contains(&ScanEvent::RootFinished { root: dir.clone() }));
+        assert_eq!(events.last(), Some(&ScanEvent::Finished { apps: 5, cancelled: false }));
+
+        let mut progress = ScanProgress::default();
+        for event in &events {
+            progress.apply(event);
+            if matches!(event, ScanEvent::RootFinished { .. }) {
+                // Totals are complete before the merged size replaces them
+                assert_eq!((progress.files_seen, progress.apps_found), (5, 5));
+            }
+        }
+        assert_eq!((progress.roots_done, progress.roots_total), (1, 1));
+        assert_eq!(progress.apps_found, 5);
+        assert!(!progress.running);
+
+        assert_eq!(indexed.lock().unwrap().len(), 5);
+        assert!(!tracker.is_scanning());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn dropping_the_handle_cancels_and_keeps_the_index() {
+        let dir = root_with_apps("cancel", 3);
+        let existing = AppSearcher::index_entry(&dir.join("tool0.app"), &[".app".to_string()], &FilterRules::new(&[], &[]));
+        let indexed = Arc::new(Mutex::new(existing.into_iter().collect::<Vec<_>>()));
+        let tracker = ScanTracker::default();
+
+        // Holding the index lock keeps the scan from finishing before the drop
+        let guard = indexed.lock().unwrap();
+        let (scan, events) = start_scan(&dir, &indexed, &tracker);
+        assert!(tracker.is_scanning());
+        drop(scan);
+        drop(guard);
+
+        let events = events_until_finished(&events);
+        assert_eq!(events.last(), Some(&ScanEvent::Finished { apps: 0, cancelled: true }));
+        assert_eq!(indexed.lock().unwrap().len(), 1);
+
+        // The tracker counts the scan until its thread is done
+        while tracker.is_scanning() {
+            thread::yield_now();
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn records_mtimes_of_entries_and_desktop_files() {
+        let dir = root_with_apps("mtimes", 1);
+        fs::write(dir.join("notes.txt"), "").unwrap();
+        fs::write(dir.join("hidden.desktop"), "[Desktop Entry]\nType=Application\nName=Hidden\nExec=true\nNoDisplay=true\n").unwrap();
+
+        let (tx, _events) = mpsc::channel();
+        let root = ScanRoot::new(dir.clone());
+        let root_scan = IndexScan::scan_root(&root, &[".app".to_string()], &FilterRules::default(), &AtomicBool::new(false), &tx);
+
+        assert_eq!(root_scan.apps.len(), 1);
+        let mut files: Vec<&PathBuf> = root_scan.mtimes.files.keys().collect();
+        files.sort();
+        assert_eq!(files, [&dir.join("hidden.desktop"), &dir.join("tool0.app")]);
+        assert!(root_scan.mtimes.directories.contains_key(&dir.join("sub")));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn tracker_counts_overlapping_scans() {
+        let tracker = ScanTracker::default();
+        let first = tracker.begin();
+        let second = tracker.begin();
+
+        drop(first);
+        assert!(tracker.is_scanning());
+        drop(second);
+        assert!(!tracker.is_scanning());
+    }
+}
@@ -1,181 +1,475 @@
-use crate::app::AppInfo;
+use crate::{
+    app_info::AppInfo,
+    config::{ScanRoot, SearchConfig},
+    filter_rules::FilterRules,
+    fuzzy::FuzzyMatcher,
+    history::LaunchHistory,
+    index_cache::IndexCache,
+    desktop_entry::DesktopEntry,
+    pe_version::VersionInfo,
+    platform,
+    scanner::{IndexScan, ScanEvent, ScanProgress, ScanTracker},
+    shell_link::ShellLink,
+    watcher::IndexWatcher,
+};
+use serde::Serialize;
 use std::{
-    collections::HashMap,
-    path::{Path, PathBuf},
-    process::Command,
-    fs,
-    sync::{Arc, Mutex},
-    thread,
-    time::SystemTime,
+    collections::{HashMap, HashSet},
+    fmt,
+    ops::Range,
+    path::Path,
+    sync::{mpsc::Receiver, Arc, Mutex},
 };
-use walkdir::WalkDir;
-use regex::Regex;
+
+/// Score and highlight ranges produced by matching a query against one app
+struct MatchResult {
+    score: f32,
+    name_matches: Vec<Range<usize>>,
+    path_matches: Vec<Range<usize>>,
+}
+
+/// Size of the index, for `index stats` and IPC clients
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexStats {
+    pub apps: usize,
+    pub files: usize, // Indexed files, counting every shortcut merged into an app
+    pub scan_roots: usize,
+    pub missing_roots: usize, // Configured roots that don't exist
+    pub scanning: bool,
+}
+
+impl fmt::Display for IndexStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} applications from {} files in {} scan roots ({} missing){}",
+            self.apps,
+            self.files,
+            self.scan_roots,
+            self.missing_roots,
+            if self.scanning { ", scan in progress" } else { "" }
+        )
+    }
+}
 
 pub struct AppSearcher {
     indexed_apps: Arc<Mutex<Vec<AppInfo>>>,
-    recent_apps: Arc<Mutex<Vec<AppInfo>>>,
-    scan_paths: Vec<PathBuf>,
-    file_extensions: Vec<String>,
-    name_regex: Regex,
+    history: Arc<Mutex<LaunchHistory>>,
+    scan_roots: Vec<ScanRoot>,
+    file_extensions: Vec<String>, // Used by roots without their own extension list
+    filter_rules: Arc<FilterRules>,
+    enable_fuzzy_search: bool,
+    watcher: Option<IndexWatcher>,
+    scan: Option<IndexScan>,
+    scans: ScanTracker,
+    scan_events: Option<Receiver<ScanEvent>>,
+    scan_progress: ScanProgress,
 }
 
 impl AppSearcher {
-    pub fn new() -> Self {
-        let scan_paths = Self::get_default_scan_paths();
-        let file_extensions = vec![
-            ".exe".to_string(),
-            ".msi".to_string(),
-            ".bat".to_string(),
-            ".cmd".to_string(),
-            ".com".to_string(),
-            ".lnk".to_string(),
-        ];
+    // Weight of launch frecency relative to text relevance
+    const FRECENCY_WEIGHT: f32 = 15.0;
+    
+    // Score for a query that prefixes one of an app's keywords
+    const KEYWORD_SCORE: f32 = 15.0;
+    
+    /// Searcher for the given settings; empty scan paths or extensions fall back
+    /// to the platform defaults
+    pub fn from_config(search_config: &SearchConfig) -> Self {
+        let (scan_roots, file_extensions, filter_rules) = Self::scan_settings(search_config);
 
         Self {
             indexed_apps: Arc::new(Mutex::new(Vec::new())),
-            recent_apps: Arc::new(Mutex::new(Vec::new())),
-            scan_paths,
+            history: Arc::new(Mutex::new(LaunchHistory::load())),
+            scan_roots,
             file_extensions,
-            name_regex: Regex::new(r"[^\w\s-_.]").unwrap(),
+            filter_rules: Arc::new(filter_rules),
+            enable_fuzzy_search: search_config.enable_fuzzy_search,
+            watcher: None,
+            scan: None,
+            scans: ScanTracker::default(),
+            scan_events: None,
+            scan_progress: ScanProgress::default(),
         }
     }
     
-    fn get_default_scan_paths() -> Vec<PathBuf> {
-        let mut paths = Vec::new();
+    /// Switch to new settings. The index is only rebuilt when the settings that
+    /// decide what gets indexed changed.
+    pub fn apply_config(&mut self, search_config: &SearchConfig) {
+        self.enable_fuzzy_search = search_config.enable_fuzzy_search;
         
-        // Common Windows application directories
-        if let Ok(program_files) = std::env::var("ProgramFiles") {
-            paths.push(PathBuf::from(program_files));
+        let (scan_roots, file_extensions, filter_rules) = Self::scan_settings(search_config);
+        let unchanged = scan_roots == self.scan_roots
+            && file_extensions == self.file_extensions
+            && filter_rules.include_rules() == self.filter_rules.include_rules()
+            && filter_rules.exclude_rules() == self.filter_rules.exclude_rules();
+        if unchanged {
+            return;
         }
         
-        if let Ok(program_files_x86) = std::env::var("ProgramFiles(x86)") {
-            paths.push(PathBuf::from(program_files_x86));
-        }
+        eprintln!("Scan settings changed, rebuilding the index");
+        self.scan_roots = scan_roots;
+        self.file_extensions = file_extensions;
+        self.filter_rules = Arc::new(filter_rules);
         
-        // Windows Apps directory
-        if let Some(local_app_data) = dirs::data_local_dir() {
-            paths.push(local_app_data.join("Microsoft").join("WindowsApps"));
-        }
+        self.watcher = IndexWatcher::start(
+            Arc::clone(&self.indexed_apps),
+            self.scan_roots.clone(),
+            self.file_extensions.clone(),
+            Arc::clone(&self.filter_rules),
+            self.scans.clone(),
+        );
+        self.rescan();
+    }
+    
+    fn scan_settings(search_config: &SearchConfig) -> (Vec<ScanRoot>, Vec<String>, FilterRules) {
+        let scan_roots = if search_config.scan_paths.is_empty() {
+            platform::current().default_scan_roots()
+        } else {
+            search_config.scan_paths.clone()
+        };
+        let file_extensions = if search_config.file_extensions.is_empty() {
+            platform::current().default_file_extensions()
+        } else {
+            search_config.file_extensions.clone()
+        };
+        let filter_rules = FilterRules::new(&search_config.include_patterns, &search_config.exclude_patterns);
         
-        // Start Menu shortcuts
-        if let Some(start_menu) = dirs::data_dir() {
-            paths.push(start_menu.join("Microsoft").join("Windows").join("Start Menu").join("Programs"));
-        }
+        (scan_roots, file_extensions, filter_rules)
+    }
+    
+    pub fn start_initial_scan(&mut self) {
+        // Serve the cached index right away, then verify it in the background
+        let cached = self.load_cached_index();
         
-        // Common shortcuts location
-        if let Some(roaming) = dirs::data_dir() {
-            paths.push(roaming.join("Microsoft").join("Windows").join("Start Menu").join("Programs"));
-        }
+        // Keep the index current once it is populated
+        self.watcher = IndexWatcher::start(
+            Arc::clone(&self.indexed_apps),
+            self.scan_roots.clone(),
+            self.file_extensions.clone(),
+            Arc::clone(&self.filter_rules),
+            self.scans.clone(),
+        );
+        
+        self.start_scan(cached);
+    }
+    
+    /// Rebuilds the index from scratch, cancelling a scan that is still running
+    pub fn rescan(&mut self) {
+        self.start_scan(None);
+    }
+    
+    /// Load the index for a one-off command, without watching for changes: the
+    /// cache if it is still fresh, otherwise a full scan. Blocks until done.
+    pub fn load_index(&mut self) {
+        let cached = self.load_cached_index();
+        self.start_scan(cached);
+        self.wait_for_scan();
+    }
+    
+    /// Rescan and wait for the new index
+    pub fn rebuild_index(&mut self) {
+        self.rescan();
+        self.wait_for_scan();
+    }
+    
+    /// Use the cached index if it was built with the current settings, returning
+    /// the cache so a scan can check it is still fresh
+    fn load_cached_index(&mut self) -> Option<IndexCache> {
+        let cached = IndexCache::load()
+            .filter(|cache| cache.matches_settings(&self.scan_roots, &self.file_extensions, &self.filter_rules));
         
-        // User's desktop
-        if let Some(desktop) = dirs::desktop_dir() {
-            paths.push(desktop);
+        if let Some(cache) = &cached {
+            if let Ok(mut indexed) = self.indexed_apps.lock() {
+                *indexed = cache.apps.clone();
+                eprintln!("Loaded {} applications from index cache", indexed.len());
+            }
         }
         
-        // System PATH directories
-        if let Ok(path_env) = std::env::var("PATH") {
-            for path_str in path_env.split(';') {
-                if !path_str.is_empty() {
-                    paths.push(PathBuf::from(path_str));
+        cached
+    }
+    
+    fn wait_for_scan(&mut self) {
+        if let Some(events) = &self.scan_events {
+            for event in events.iter() {
+                self.scan_progress.apply(&event);
+                if matches!(event, ScanEvent::Finished { .. }) {
+                    break;
                 }
             }
         }
-        
-        paths
     }
     
-    pub fn start_initial_scan(&mut self) {
-        let indexed_apps = Arc::clone(&self.indexed_apps);
-        let scan_paths = self.scan_paths.clone();
-        let file_extensions = self.file_extensions.clone();
+    fn start_scan(&mut self, cached: Option<IndexCache>) {
+        // Replacing the handle cancels the previous scan
+        let (scan, events) = IndexScan::start(
+            Arc::clone(&self.indexed_apps),
+            self.scan_roots.clone(),
+            self.file_extensions.clone(),
+            Arc::clone(&self.filter_rules),
+            cached,
+            &self.scans,
+        );
         
-        // Start background scanning thread
-        thread::spawn(move || {
-            let apps = Self::scan_for_applications(&scan_paths, &file_extensions);
-            
-            if let Ok(mut indexed) = indexed_apps.lock() {
-                *indexed = apps;
-                println!("App indexing completed. Found {} applications", indexed.len());
+        self.scan = Some(scan);
+        self.scan_events = Some(events);
+        self.scan_progress = ScanProgress::default();
+    }
+    
+    /// Applies pending scan events and returns the current progress
+    pub fn poll_scan_progress(&mut self) -> &ScanProgress {
+        if let Some(events) = &self.scan_events {
+            for event in events.try_iter() {
+                self.scan_progress.apply(&event);
             }
-        });
+        }
+        
+        &self.scan_progress
     }
     
-    fn scan_for_applications(scan_paths: &[PathBuf], file_extensions: &[String]) -> Vec<AppInfo> {
-        let mut apps = Vec::new();
-        let mut seen_names = HashMap::new();
+    pub fn index_stats(&self) -> IndexStats {
+        let (apps, files) = match self.indexed_apps.lock() {
+            Ok(indexed) => (indexed.len(), indexed.iter().map(|app| app.sources.len().max(1)).sum()),
+            Err(_) => (0, 0),
+        };
         
-        for base_path in scan_paths {
-            if !base_path.exists() {
-                continue;
-            }
-            
-            println!("Scanning: {:?}", base_path);
-            
-            let walker = WalkDir::new(base_path)
-                .max_depth(3) // Limit depth to avoid too much scanning
-                .follow_links(false)
-                .into_iter()
-                .filter_map(|e| e.ok());
-                
-            for entry in walker {
-                let path = entry.path();
-                
-                if !path.is_file() {
-                    continue;
-                }
-                
-                if let Some(extension) = path.extension() {
-                    let ext = format!(".{}", extension.to_string_lossy().to_lowercase());
-                    
-                    if file_extensions.contains(&ext) {
-                        if let Some(app_info) = Self::create_app_info(path) {
-                            // Avoid duplicates based on name
-                            let key = app_info.name.to_lowercase();
-                            if !seen_names.contains_key(&key) {
-                                seen_names.insert(key, true);
-                                apps.push(app_info);
-                            }
-                        }
-                    }
-                }
-            }
+        IndexStats {
+            apps,
+            files,
+            scan_roots: self.scan_roots.len(),
+            missing_roots: self.scan_roots.iter().filter(|root| !root.path.exists()).count(),
+            scanning: self.scan_progress.running,
+        }
+    }
+    
+    /// The app meant by a path or name given on the command line or over IPC: the
+    /// indexed entry for an existing file, else an app with exactly that name,
+    /// else the best search hit
+    pub fn resolve_app(&self, name_or_path: &str) -> Option<AppInfo> {
+        let path = Path::new(name_or_path);
+        let indexed = self.indexed_apps.lock().ok()?;
+        
+        if path.exists() {
+            let found = indexed
+                .iter()
+                .find(|app| app.path == name_or_path || app.sources.iter().any(|source| source == name_or_path));
+            return found.cloned().or_else(|| Self::create_app_info(path));
         }
         
-        // Sort by name for better organization
-        apps.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        if let Some(app) = indexed.iter().find(|app| app.name.eq_ignore_ascii_case(name_or_path)) {
+            return Some(app.clone());
+        }
+        drop(indexed);
         
-        apps
+        self.search(name_or_path, 1).into_iter().next()
     }
     
-    fn create_app_info(path: &Path) -> Option<AppInfo> {
-        let file_name = path.file_stem()?.to_string_lossy();
+    /// Builds the index entry for `path` if the platform considers it launchable
+    /// and the filter rules keep it
+    pub fn index_entry(path: &Path, file_extensions: &[String], filter_rules: &FilterRules) -> Option<AppInfo> {
+        if platform::current().is_executable(path, file_extensions) {
+            Self::create_app_info(path).filter(|app_info| filter_rules.allows(app_info))
+        } else {
+            None
+        }
+    }
+    
+    /// Explains why `path` is or isn't indexed, naming the filter rule that hid it
+    pub fn explain_filter(&self, path: &Path) -> String {
+        if !path.exists() {
+            return "File does not exist".to_string();
+        }
+        
+        let Some(root) = ScanRoot::closest(&self.scan_roots, path) else {
+            return "Not indexed: outside every scan root".to_string();
+        };
+        
+        if !root.covers(path) {
+            return format!("Not indexed: deeper than max_depth {} or hidden below {:?}", root.max_depth, root.path);
+        }
         
-        // Skip common system files and uninteresting executables
-        let skip_patterns = [
-            "unins", "uninst", "setup", "install", "update", "crash", "error",
-            "helper", "service", "daemon", "background", "launcher"
-        ];
+        if !platform::current().is_executable(path, root.extensions(&self.file_extensions)) {
+            return "Not indexed: file type is not scanned and the file is not executable".to_string();
+        }
         
-        let name_lower = file_name.to_lowercase();
-        if skip_patterns.iter().any(|pattern| name_lower.contains(pattern)) {
-            return None;
+        match Self::create_app_info(path) {
+            Some(app_info) => format!("{}: {}", app_info.name, self.filter_rules.evaluate(&app_info)),
+            None if path.extension().is_some_and(|ext| ext == "desktop") => {
+                "Not indexed: hidden or unreadable desktop entry".to_string()
+            }
+            None => "Not indexed: the file has no name".to_string(),
         }
+    }
+    
+    fn create_app_info(path: &Path) -> Option<AppInfo> {
+        if path.extension().is_some_and(|ext| ext == "desktop") {
+            let entry = DesktopEntry::load(path)?;
+            return entry.is_visible().then(|| entry.to_app_info(path));
+        }
+        
+        let file_name = path.file_stem()?.to_string_lossy();
         
         // Clean up the display name
         let clean_name = Self::clean_app_name(&file_name);
         
-        // Try to get file description or version info on Windows
-        let description = Self::get_file_description(path);
+        // Shortcuts are resolved so they carry their target and its description
+        let link = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("lnk")) {
+            ShellLink::load(path)
+        } else {
+            None
+        };
+        
+        // Resolve shortcuts and symlinks so duplicates of one program can be merged
+        let target = match link.as_ref() {
+            Some(link) => link.target_path.as_ref().map(|target| {
+                platform::canonical_path(Path::new(target))
+                    .map(|resolved| resolved.to_string_lossy().to_string())
+                    .unwrap_or_else(|| target.clone())
+            }),
+            None => platform::canonical_path(path)
+                .map(|resolved| resolved.to_string_lossy().to_string())
+                .filter(|resolved| Path::new(resolved) != path),
+        };
+        
+        // Version resources of the target (or the file itself) describe the program
+        let version_info = target
+            .as_deref()
+            .and_then(|target| Self::read_version_info(Path::new(target)))
+            .or_else(|| Self::read_version_info(path));
+        
+        let description = link
+            .as_ref()
+            .and_then(|link| link.description.clone())
+            .or_else(|| version_info.as_ref().and_then(|info| info.description()));
+        
+        // The product name often differs from the file name, e.g. "Code.exe"
+        let keywords = version_info
+            .as_ref()
+            .and_then(|info| info.product_name.clone())
+            .filter(|product| !product.eq_ignore_ascii_case(&clean_name))
+            .into_iter()
+            .collect();
         
         Some(AppInfo {
             name: clean_name,
             path: path.to_string_lossy().to_string(),
-            icon_path: None, // TODO: Extract icon
+            target,
+            sources: vec![path.to_string_lossy().to_string()],
+            path_hint: None,
+            icon_path: link.as_ref().and_then(|link| link.icon_resource()), // TODO: Extract icon for other files
             description,
+            hotkey: link.and_then(|link| link.hotkey),
+            keywords,
+            version_info,
             score: 0.0,
+            name_matches: Vec::new(),
+            path_matches: Vec::new(),
         })
     }
     
+    /// Identifies the program an entry launches: its resolved target, or the path itself
+    pub fn dedupe_key(app: &AppInfo) -> String {
+        let key = app.target.as_deref().unwrap_or(&app.path);
+        
+        // Windows paths are case-insensitive
+        if cfg!(windows) {
+            key.to_lowercase()
+        } else {
+            key.to_string()
+        }
+    }
+    
+    /// Launch history is recorded per program rather than per file, so it stays
+    /// with a merged app whichever of its sources ends up as the primary entry
+    fn history_key(app: &AppInfo) -> String {
+        Self::dedupe_key(app)
+    }
+    
+    /// Frecency of `app`, including launches recorded under the path of one of its
+    /// sources by versions that keyed history on the primary entry's path
+    fn frecency(history: &LaunchHistory, app: &AppInfo) -> f64 {
+        let key = Self::history_key(app);
+        let legacy = app.sources.iter().chain(std::iter::once(&app.path));
+        let mut paths: Vec<&str> = legacy.map(String::as_str).filter(|path| *path != key).collect();
+        paths.sort_unstable();
+        paths.dedup();
+        
+        history.frecency(&key) + paths.into_iter().map(|path| history.frecency(path)).sum::<f64>()
+    }
+    
+    /// Merges entries that launch the same program into one entry listing all of
+    /// its sources, then gives same-named entries a path hint to tell them apart
+    pub fn merge_duplicates(apps: Vec<AppInfo>) -> Vec<AppInfo> {
+        let mut merged: Vec<AppInfo> = Vec::new();
+        let mut by_key: HashMap<String, usize> = HashMap::new();
+        
+        for app in apps {
+            let key = Self::dedupe_key(&app);
+            match by_key.get(&key) {
+                Some(&index) => Self::merge_into(&mut merged[index], app),
+                None => {
+                    by_key.insert(key, merged.len());
+                    merged.push(app);
+                }
+            }
+        }
+        
+        Self::assign_path_hints(&mut merged);
+        merged
+    }
+    
+    /// Folds `app` into `existing`, which launches the same program. Shortcuts and
+    /// desktop entries carry more metadata than a bare executable, so they stand in
+    /// for it; otherwise the entry found first is kept. The other entry's name stays
+    /// searchable as a keyword, so aliases like `nawk` for `awk` still match.
+    pub fn merge_into(existing: &mut AppInfo, mut app: AppInfo) {
+        if Self::is_shortcut(&app) && !Self::is_shortcut(existing) {
+            std::mem::swap(existing, &mut app);
+        }
+        
+        for source in app.sources {
+            if !existing.sources.contains(&source) {
+                existing.sources.push(source);
+            }
+        }
+        
+        for keyword in std::iter::once(app.name).chain(app.keywords) {
+            if !keyword.eq_ignore_ascii_case(&existing.name) && !existing.keywords.contains(&keyword) {
+                existing.keywords.push(keyword);
+            }
+        }
+    }
+    
+    /// Sets `path_hint` on apps whose name is shared with a different app
+    pub fn assign_path_hints(apps: &mut [AppInfo]) {
+        let mut name_counts: HashMap<String, usize> = HashMap::new();
+        for app in apps.iter() {
+            *name_counts.entry(app.name.to_lowercase()).or_default() += 1;
+        }
+        
+        for app in apps.iter_mut() {
+            let is_shared = name_counts.get(&app.name.to_lowercase()).is_some_and(|count| *count > 1);
+            app.path_hint = is_shared.then(|| Self::path_hint(app));
+        }
+    }
+    
+    /// Directory of the program, with the home directory abbreviated to `~`
+    fn path_hint(app: &AppInfo) -> String {
+        let program = Path::new(app.target.as_deref().unwrap_or(&app.path));
+        let dir = program.parent().unwrap_or(program);
+        
+        match dirs::home_dir().and_then(|home| dir.strip_prefix(home).ok().map(Path::to_path_buf)) {
+            Some(relative) => Path::new("~").join(relative).to_string_lossy().to_string(),
+            None => dir.to_string_lossy().to_string(),
+        }
+    }
+    
+    fn is_shortcut(app: &AppInfo) -> bool {
+        Path::new(&app.path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("lnk") || ext == "desktop")
+    }
+    
     fn clean_app_name(raw_name: &str) -> String {
         // Remove common suffixes and clean up name
         let cleaned = raw_name
@@ -197,16 +491,17 @@ impl AppSearcher {
             .join(" ")
     }
     
-    #[cfg(windows)]
-    fn get_file_description(path: &Path) -> Option<String> {
-        // On Windows, we could use Windows API to get file version info
-        // For now, return None, but this could be enhanced
-        None
-    }
-    
-    #[cfg(not(windows))]
-    fn get_file_description(_path: &Path) -> Option<String> {
-        None
+    /// Version resource of a PE executable; works for Wine prefixes on any platform
+    fn read_version_info(path: &Path) -> Option<VersionInfo> {
+        let is_pe = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("exe") || ext.eq_ignore_ascii_case("dll"));
+        
+        if is_pe {
+            VersionInfo::load(path)
+        } else {
+            None
+        }
     }
     
     pub fn search(&self, query: &str, limit: usize) -> Vec<AppInfo> {
@@ -214,15 +509,38 @@ impl AppSearcher {
             return self.get_recent_apps(limit);
         }
         
-        let indexed_apps = self.indexed_apps.lock().ok()?;
+        let indexed_apps = match self.indexed_apps.lock() {
+            Ok(indexed) => indexed,
+            Err(_) => return Vec::new(),
+        };
+        let history = match self.history.lock() {
+            Ok(history) => history,
+            Err(_) => return Vec::new(),
+        };
         let query_lower = query.to_lowercase();
         
-        let mut scored_results: Vec<(AppInfo, f32)> = indexed_apps
+        let mut scored_results: Vec<(AppInfo, MatchResult)> = indexed_apps
             .iter()
             .filter_map(|app| {
-                let score = Self::calculate_relevance_score(&app.name, &app.path, &query_lower);
-                if score > 0.0 {
-                    Some((app.clone(), score))
+                let mut result = if self.enable_fuzzy_search {
+                    Self::calculate_fuzzy_score(&app.name, &app.path, &query_lower)
+                } else {
+                    Self::calculate_relevance_score(&app.name, &app.path, &query_lower)
+                };
+                
+                // Keywords count for less than the name itself
+                if app.keywords.iter().any(|k| k.to_lowercase().starts_with(&query_lower)) {
+                    result.score += Self::KEYWORD_SCORE;
+                }
+                if result.score > 0.0 {
+                    // Frequently and recently launched apps rise above similar names
+                    result.score += Self::FRECENCY_WEIGHT * (Self::frecency(&history, app) as f32).ln_1p();
+                    
+                    // Roots can be configured to rank their apps higher
+                    if let Some(root) = ScanRoot::closest(&self.scan_roots, Path::new(&app.path)) {
+                        result.score += root.priority_boost;
+                    }
+                    Some((app.clone(), result))
                 } else {
                     None
                 }
@@ -230,20 +548,22 @@ impl AppSearcher {
             .collect();
         
         // Sort by score (highest first)
-        scored_results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored_results.sort_by(|a, b| b.1.score.partial_cmp(&a.1.score).unwrap_or(std::cmp::Ordering::Equal));
         
-        // Update scores and return results
+        // Update scores and highlight ranges, then return results
         scored_results
             .into_iter()
             .take(limit)
-            .map(|(mut app, score)| {
-                app.score = score;
+            .map(|(mut app, result)| {
+                app.score = result.score;
+                app.name_matches = result.name_matches;
+                app.path_matches = result.path_matches;
                 app
             })
             .collect()
     }
     
-    fn calculate_relevance_score(name: &str, path: &str, query: &str) -> f32 {
+    fn calculate_relevance_score(name: &str, path: &str, query: &str) -> MatchResult {
         let name_lower = name.to_lowercase();
         let path_lower = path.to_lowercase();
         let mut score = 0.0;
@@ -273,12 +593,72 @@ impl AppSearcher {
             score += 20.0 / (name.len() as f32 + 1.0);
         }
         
-        score
+        MatchResult {
+            score,
+            name_matches: FuzzyMatcher::substring_range(name, query).into_iter().collect(),
+            path_matches: FuzzyMatcher::substring_range(path, query).into_iter().collect(),
+        }
+    }
+    
+    fn calculate_fuzzy_score(name: &str, path: &str, query: &str) -> MatchResult {
+        let name_match = FuzzyMatcher::fuzzy_match(name, query);
+        let mut score = name_match.as_ref().map_or(0.0, |m| m.score);
+        
+        // Exact match still wins over any fuzzy alignment
+        if name.to_lowercase() == query {
+            score += 100.0;
+        }
+        
+        // Path contains query gets lower score
+        if path.to_lowercase().contains(query) {
+            score += 10.0;
+        }
+        
+        // Bonus for shorter names (more specific matches)
+        if score > 0.0 {
+            score += 20.0 / (name.len() as f32 + 1.0);
+        }
+        
+        MatchResult {
+            score,
+            name_matches: name_match
+                .map(|m| FuzzyMatcher::positions_to_ranges(&m.positions))
+                .unwrap_or_default(),
+            path_matches: FuzzyMatcher::substring_range(path, query).into_iter().collect(),
+        }
     }
     
     pub fn get_recent_apps(&self, limit: usize) -> Vec<AppInfo> {
-        if let Ok(recent) = self.recent_apps.lock() {
-            recent.iter().take(limit).cloned().collect()
+        if let (Ok(indexed), Ok(history)) = (self.indexed_apps.lock(), self.history.lock()) {
+            // Launches of one app can be recorded under several of its paths
+            let mut seen = HashSet::new();
+            history
+                .most_recent(usize::MAX)
+                .into_iter()
+                .map(|(path, stats)| {
+                    // Prefer the indexed entry, but keep apps that are no longer indexed
+                    let found = indexed.iter().find(|app| {
+                        Self::history_key(app) == path || app.path == path || app.sources.iter().any(|source| source == path)
+                    });
+                    found.cloned().unwrap_or_else(|| AppInfo {
+                        name: stats.name.clone(),
+                        path: path.to_string(),
+                        target: None,
+                        sources: vec![path.to_string()],
+                        path_hint: None,
+                        icon_path: None,
+                        description: None,
+                        hotkey: None,
+                        keywords: Vec::new(),
+                        version_info: None,
+                        score: 0.0,
+                        name_matches: Vec::new(),
+                        path_matches: Vec::new(),
+                    })
+                })
+                .filter(|app| seen.insert(app.path.clone()))
+                .take(limit)
+                .collect()
         } else {
             // Fallback to some popular apps from index
             if let Ok(indexed) = self.indexed_apps.lock() {
@@ -289,35 +669,119 @@ impl AppSearcher {
         }
     }
     
-    pub fn add_to_recent(&self, app: AppInfo) {
-        if let Ok(mut recent) = self.recent_apps.lock() {
-            // Remove if already exists
-            recent.retain(|a| a.path != app.path);
-            
-            // Add to front
-            recent.insert(0, app);
+    pub fn add_to_recent(&self, app: &AppInfo) {
+        if let Ok(mut history) = self.history.lock() {
+            history.record_launch(&app.name, &Self::history_key(app));
             
-            // Keep only last 20
-            recent.truncate(20);
+            if let Err(e) = history.save() {
+                eprintln!("Failed to save launch history: {}", e);
+            }
         }
     }
     
     pub fn launch_app(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Launching: {}", path);
+        eprintln!("Launching: {}", path);
         
-        let path_buf = PathBuf::from(path);
-        
-        if path_buf.extension().and_then(|s| s.to_str()) == Some("lnk") {
-            // For shortcuts, use Windows shell to open
-            Command::new("cmd")
-                .args(&["/C", "start", "", path])
-                .spawn()?;
-        } else {
-            // For executables, launch directly
-            Command::new(path)
-                .spawn()?;
+        platform::current().launch(Path::new(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Index entry for `path`, launching `target` if it is a shortcut
+    fn app(name: &str, path: &str, target: Option<&str>) -> AppInfo {
+        AppInfo {
+            name: name.to_string(),
+            path: path.to_string(),
+            target: target.map(str::to_string),
+            sources: vec![path.to_string()],
+            path_hint: None,
+            icon_path: None,
+            description: None,
+            hotkey: None,
+            keywords: Vec::new(),
+            version_info: None,
+            score: 0.0,
+            name_matches: Vec::new(),
+            path_matches: Vec::new(),
         }
-        
-        Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn merges_entries_that_launch_the_same_program() {
+        let merged = AppSearcher::merge_duplicates(vec![
+            app("Gawk", "/usr/bin/gawk", None),
+            app("Awk", "/usr/bin/awk", Some("/usr/bin/gawk")),
+            app("Sed", "/usr/bin/sed", None),
+        ]);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].path, "/usr/bin/gawk");
+        assert_eq!(merged[0].sources, ["/usr/bin/gawk", "/usr/bin/awk"]);
+        assert_eq!(merged[0].keywords, ["Awk"]);
+        assert_eq!(merged[1].path, "/usr/bin/sed");
+    }
+
+    #[test]
+    fn shortcuts_stand_in_for_the_program_they_launch() {
+        let binary = app("Gimp", "/usr/bin/gimp", None);
+        let desktop = app("GNU Image Manipulation Program", "/usr/share/applications/gimp.desktop", Some("/usr/bin/gimp"));
+
+        // Whichever is found first, the desktop entry is the primary one
+        for apps in [vec![binary.clone(), desktop.clone()], vec![desktop.clone(), binary.clone()]] {
+            let merged = AppSearcher::merge_duplicates(apps);
+            assert_eq!(merged.len(), 1);
+            assert_eq!(merged[0].name, "GNU Image Manipulation Program");
+            assert_eq!(merged[0].path, "/usr/share/applications/gimp.desktop");
+            assert_eq!(merged[0].keywords, ["Gimp"]);
+            assert_eq!(merged[0].sources.len(), 2);
+        }
+
+        // Otherwise the first one found is kept
+        let other = app("Gimp Launcher", "/usr/share/applications/gimp-2.10.desktop", Some("/usr/bin/gimp"));
+        let merged = AppSearcher::merge_duplicates(vec![desktop, other]);
+        assert_eq!(merged[0].path, "/usr/share/applications/gimp.desktop");
+        assert_eq!(merged[0].keywords, ["Gimp Launcher"]);
+    }
+
+    #[test]
+    fn same_named_apps_get_path_hints() {
+        let merged = AppSearcher::merge_duplicates(vec![
+            app("Python", "/usr/bin/python", None),
+            app("python", "/opt/python/bin/python", None),
+            app("Perl", "/usr/bin/perl", None),
+        ]);
+
+        assert_eq!(merged[0].path_hint.as_deref(), Some("/usr/bin"));
+        assert_eq!(merged[1].path_hint.as_deref(), Some("/opt/python/bin"));
+        assert_eq!(merged[2].path_hint, None);
+    }
+
+    #[test]
+    fn history_follows_the_program_when_the_primary_entry_changes() {
+        let binary = app("Gimp", "/usr/bin/gimp", None);
+        let desktop = app("GNU Image Manipulation Program", "/usr/share/applications/gimp.desktop", Some("/usr/bin/gimp"));
+
+        // Launched before its desktop entry was installed
+        let mut history = LaunchHistory::default();
+        history.record_launch(&binary.name, &AppSearcher::history_key(&binary));
+
+        let merged = AppSearcher::merge_duplicates(vec![binary, desktop]);
+        assert_eq!(merged[0].path, "/usr/share/applications/gimp.desktop");
+        assert!(AppSearcher::frecency(&history, &merged[0]) > 0.9);
+    }
+
+    #[test]
+    fn history_recorded_under_a_source_path_still_counts() {
+        let mut history = LaunchHistory::default();
+        history.record_launch("GNU Image Manipulation Program", "/usr/share/applications/gimp.desktop");
+
+        let merged = AppSearcher::merge_duplicates(vec![
+            app("Gimp", "/usr/bin/gimp", None),
+            app("GNU Image Manipulation Program", "/usr/share/applications/gimp.desktop", Some("/usr/bin/gimp")),
+        ]);
+        assert!(AppSearcher::frecency(&history, &merged[0]) > 0.9);
+    }
+}
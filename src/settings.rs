@@ -0,0 +1,443 @@
+use crate::{
+    config::{Config, ScanRoot},
+    config_format::ConfigFormat,
+    filter_rules::FilterRule,
+};
+use serde_json::{Map, Value};
+use std::{fs, path::PathBuf};
+
+/// Typed access to single settings by dotted path, such as `search.max_results`
+/// or `search.scan_paths[0].max_depth`, shared by the settings window, the CLI
+/// and IPC. Paths name fields as they appear in the config file. Values are
+/// checked by deserializing the changed config and validating it, so a change
+/// that is rejected leaves the config untouched.
+impl Config {
+    /// Current value of the setting at `path`
+    pub fn get_setting(&self, path: &str) -> Result<Value, String> {
+        let value = self.to_json()?;
+        let pointer = Self::setting_pointer(path)?;
+
+        value
+            .pointer(&pointer)
+            .cloned()
+            .ok_or_else(|| format!("Unknown setting: {}", path))
+    }
+
+    /// Replace the setting at `path`. Optional fields may be set to `null`.
+    pub fn set_setting(&mut self, path: &str, new_value: Value) -> Result<(), String> {
+        self.edit_setting(path, |current| {
+            *current = new_value;
+            Ok(())
+        })
+    }
+
+    /// Append `item` to the list at `path`, e.g. a scan path or exclude pattern.
+    /// Items take the same shorthands as in the config file.
+    pub fn add_setting(&mut self, path: &str, item: Value) -> Result<(), String> {
+        self.edit_setting(path, |current| match current {
+            Value::Array(items) => {
+                items.push(item);
+                Ok(())
+            }
+            Value::Null => {
+                *current = Value::Array(vec![item]);
+                Ok(())
+            }
+            _ => Err(format!("not a list but {}", Self::type_name(current))),
+        })
+    }
+
+    /// Remove every entry equal to `item` from the list at `path`. A plain string
+    /// also matches a scan root or filter rule with that path or pattern.
+    pub fn remove_setting(&mut self, path: &str, item: &Value) -> Result<(), String> {
+        // Compare in the form the config would store the item in
+        let mut probe = self.clone();
+        probe.add_setting(path, item.clone())?;
+        let stored = match probe.get_setting(path)? {
+            Value::Array(mut items) => items.pop(),
+            _ => None,
+        };
+
+        self.edit_setting(path, |current| {
+            let Value::Array(items) = current else {
+                return Err(format!("not a list but {}", Self::type_name(current)));
+            };
+
+            let before = items.len();
+            items.retain(|entry| {
+                let shorthand = ["path", "pattern"].iter().any(|key| entry.get(key) == Some(item));
+                Some(entry) != stored.as_ref() && entry != item && !shorthand
+            });
+
+            if items.len() == before {
+                Err(format!("{} is not in the list", item))
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    /// Save the setting at `path` to the config file, leaving the rest of the
+    /// file as it is written: shorthands stay short and defaults stay unset.
+    /// Without a config file, the whole config is saved.
+    pub fn save_setting(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(config_path) = Self::get_config_path() else {
+            return Ok(());
+        };
+        let Ok(existing) = fs::read_to_string(&config_path) else {
+            return self.save_to(&config_path);
+        };
+        self.ensure_valid()?;
+
+        let format = ConfigFormat::from_path(&config_path).unwrap_or(ConfigFormat::Json);
+        fs::write(config_path, self.setting_written_to(path, &existing, format)?)?;
+        Ok(())
+    }
+
+    /// `existing`, a config file in `format`, with the setting at `path` changed
+    /// to its value in this config
+    fn setting_written_to(&self, path: &str, existing: &str, format: ConfigFormat) -> Result<String, String> {
+        let mut file = format.parse(existing)?;
+        // What the file's entries mean, to tell which list entries are unchanged
+        let written = Self::parse(existing, format).and_then(|config| config.to_json()).ok();
+
+        let pointer = Self::setting_pointer(path)?;
+        let segments: Vec<&str> = pointer.split('/').skip(1).collect();
+        Self::write_setting(&mut file, written.as_ref(), &self.to_json()?, &segments);
+
+        format.write(&file, Some(existing))
+    }
+
+    /// Copy the setting at `segments` from `config` into `file`, the config file
+    /// in the JSON data model. Missing sections are added; a shorthand in the way,
+    /// such as a scan root given as a plain path, is written out in full. `written`
+    /// is what `file` means, so list entries that didn't change keep their form.
+    fn write_setting(file: &mut Value, written: Option<&Value>, config: &Value, segments: &[&str]) {
+        let Some((segment, rest)) = segments.split_first() else {
+            *file = match (&*file, written, config) {
+                (Value::Array(entries), Some(Value::Array(meanings)), Value::Array(items)) => {
+                    Value::Array(Self::keep_entries(entries, meanings, items))
+                }
+                _ => config.clone(),
+            };
+            return;
+        };
+
+        let child = |value: &Value| match value {
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|index| items.get(index)).cloned(),
+            _ => value.get(segment).cloned(),
+        };
+        let config_child = child(config).unwrap_or(Value::Null);
+        let written_child = written.and_then(child);
+
+        match file {
+            // Unset optional fields are left out rather than written as null
+            Value::Object(object) if rest.is_empty() && config_child.is_null() => {
+                object.remove(*segment);
+            }
+            Value::Object(object) => {
+                let slot = object.entry(*segment).or_insert_with(|| match &config_child {
+                    Value::Object(_) => Value::Object(Map::new()),
+                    _ => config_child.clone(),
+                });
+                Self::write_setting(slot, written_child.as_ref(), &config_child, rest);
+            }
+            Value::Array(items) => match segment.parse::<usize>().ok().and_then(|index| items.get_mut(index)) {
+                Some(item) => Self::write_setting(item, written_child.as_ref(), &config_child, rest),
+                None => *file = config.clone(),
+            },
+            _ => *file = config.clone(),
+        }
+    }
+
+    /// The list `items`, reusing the file's own entry wherever one means the same
+    fn keep_entries(entries: &[Value], meanings: &[Value], items: &[Value]) -> Vec<Value> {
+        let mut unused: Vec<(&Value, &Value)> = entries.iter().zip(meanings).collect();
+
+        items
+            .iter()
+            .map(|item| match unused.iter().position(|(_, meaning)| *meaning == item) {
+                Some(position) => unused.remove(position).0.clone(),
+                None => Self::shorthand(item),
+            })
+            .collect()
+    }
+
+    /// A filter rule or scan root that only sets its pattern or path, written as
+    /// that plain string like the config file allows
+    fn shorthand(item: &Value) -> Value {
+        let Some(text) = item.get("pattern").or_else(|| item.get("path")).and_then(Value::as_str) else {
+            return item.clone();
+        };
+
+        let rule = serde_json::to_value(FilterRule::substring(text)).ok();
+        let root = serde_json::to_value(ScanRoot::new(PathBuf::from(text))).ok();
+        if [rule, root].iter().flatten().any(|plain| plain == item) {
+            Value::String(text.to_string())
+        } else {
+            item.clone()
+        }
+    }
+
+    /// Value typed by a user: JSON, or else the text itself as a string, so `neon`
+    /// works as well as `"neon"`
+    pub fn parse_setting_value(text: &str) -> Value {
+        serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+    }
+
+    /// Change the JSON form of the setting at `path`, then rebuild and validate the
+    /// config from it
+    fn edit_setting(&mut self, path: &str, edit: impl FnOnce(&mut Value) -> Result<(), String>) -> Result<(), String> {
+        let pointer = Self::setting_pointer(path)?;
+        let mut value = self.to_json()?;
+
+        let current = match value.pointer_mut(&pointer) {
+            Some(current) => current,
+            None => Self::optional_field(&mut value, &pointer).ok_or_else(|| format!("Unknown setting: {}", path))?,
+        };
+        edit(current).map_err(|e| format!("{}: {}", path, e))?;
+
+        let config: Config = serde_json::from_value(value).map_err(|e| format!("{}: {}", path, e))?;
+        let problems: Vec<String> = config
+            .validate()
+            .iter()
+            .filter(|problem| problem.path.starts_with(path))
+            .map(ToString::to_string)
+            .collect();
+        if !problems.is_empty() {
+            return Err(problems.join("; "));
+        }
+
+        *self = config;
+        Ok(())
+    }
+
+    /// The config in the JSON data model all config formats share
+    pub fn to_json(&self) -> Result<Value, String> {
+        // Going through text keeps `0.95` from turning into the f64 nearest the f32
+        let text = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        serde_json::from_str(&text).map_err(|e| e.to_string())
+    }
+
+    /// Optional fields left out of the JSON form while unset, such as the
+    /// extensions of a scan root, are added as `null` so they can be set
+    fn optional_field<'a>(value: &'a mut Value, pointer: &str) -> Option<&'a mut Value> {
+        const OPTIONAL_FIELDS: [&str; 1] = ["extensions"];
+
+        let (parent, field) = pointer.rsplit_once('/')?;
+        if !OPTIONAL_FIELDS.contains(&field) || !parent.contains("/scan_paths/") {
+            return None;
+        }
+
+        let object = value.pointer_mut(parent)?.as_object_mut()?;
+        Some(object.entry(field).or_insert(Value::Null))
+    }
+
+    /// JSON pointer for a dotted path; `[n]` indexes into a list
+    fn setting_pointer(path: &str) -> Result<String, String> {
+        let invalid = || format!("Invalid setting path: {}", path);
+        let mut pointer = String::new();
+
+        for segment in path.split('.') {
+            let (name, mut rest) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(invalid());
+            }
+            pointer.push('/');
+            pointer.push_str(name);
+
+            while let Some(tail) = rest.strip_prefix('[') {
+                let (index, tail) = tail.split_once(']').ok_or_else(invalid)?;
+                let index: usize = index.parse().map_err(|_| invalid())?;
+                pointer.push_str(&format!("/{}", index));
+                rest = tail;
+            }
+            if !rest.is_empty() {
+                return Err(invalid());
+            }
+        }
+
+        Ok(pointer)
+    }
+
+    fn type_name(value: &Value) -> &'static str {
+        match value {
+            Value::Null => "null",
+            Value::Bool(_) => "a boolean",
+            Value::Number(_) => "a number",
+            Value::String(_) => "a string",
+            Value::Array(_) => "a list",
+            Value::Object(_) => "a section",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const TOML: &str = r#"# Hand-written
+schema_version = 2
+
+[ui]
+window_width = 900.0 # fits the laptop
+
+[search]
+scan_paths = ["/usr/bin", "/opt/apps"]
+exclude_patterns = ["unins"]
+"#;
+
+    fn edited(path: &str, existing: &str, format: ConfigFormat, edit: impl FnOnce(&mut Config)) -> String {
+        let mut config = Config::parse(existing, format).unwrap();
+        edit(&mut config);
+        config.setting_written_to(path, existing, format).unwrap()
+    }
+
+    #[test]
+    fn saving_a_setting_changes_only_its_key() {
+        let written = edited("ui.window_width", TOML, ConfigFormat::Toml, |config| {
+            config.set_setting("ui.window_width", json!(1000)).unwrap();
+        });
+        assert_eq!(written, TOML.replace("900.0", "1000.0"));
+
+        // A missing section gets just the one key, not every default
+        let written = edited("appearance.show_particles", TOML, ConfigFormat::Toml, |config| {
+            config.set_setting("appearance.show_particles", json!(false)).unwrap();
+        });
+        assert_eq!(written, format!("{}\n[appearance]\nshow_particles = false\n", TOML));
+    }
+
+    #[test]
+    fn saving_a_list_keeps_shorthand_entries() {
+        let written = edited("search.exclude_patterns", TOML, ConfigFormat::Toml, |config| {
+            config.add_setting("search.exclude_patterns", json!("setup")).unwrap();
+        });
+        assert_eq!(written, TOML.replace(r#"["unins"]"#, r#"["unins", "setup"]"#));
+
+        let written = edited("search.scan_paths", TOML, ConfigFormat::Toml, |config| {
+            config.add_setting("search.scan_paths", json!({"path": "/snap/bin", "max_depth": 1})).unwrap();
+            config.remove_setting("search.scan_paths", &json!("/usr/bin")).unwrap();
+        });
+        let file = ConfigFormat::Toml.parse(&written).unwrap();
+        assert_eq!(file["search"]["scan_paths"][0], json!("/opt/apps"));
+        assert_eq!(file["search"]["scan_paths"][1]["path"], json!("/snap/bin"));
+        assert_eq!(file["search"]["scan_paths"][1]["max_depth"], json!(1));
+
+        // A field of a shorthand scan root writes that root out in full
+        let written = edited("search.scan_paths[1].max_depth", TOML, ConfigFormat::Toml, |config| {
+            config.set_setting("search.scan_paths[1].max_depth", json!(2)).unwrap();
+        });
+        let file = ConfigFormat::Toml.parse(&written).unwrap();
+        assert_eq!(file["search"]["scan_paths"][0], json!("/usr/bin"));
+        assert_eq!(file["search"]["scan_paths"][1]["path"], json!("/opt/apps"));
+        assert_eq!(file["search"]["scan_paths"][1]["max_depth"], json!(2));
+        assert!(written.starts_with("# Hand-written\n"));
+    }
+
+    #[test]
+    fn saving_an_unset_optional_field_removes_it() {
+        let json = r#"{"schema_version": 2, "search": {"scan_paths": [{"path": "/usr/bin", "extensions": [".sh"]}]}}"#;
+        let written = edited("search.scan_paths[0].extensions", json, ConfigFormat::Json, |config| {
+            config.set_setting("search.scan_paths[0].extensions", Value::Null).unwrap();
+        });
+
+        let file: Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(file, json!({"schema_version": 2, "search": {"scan_paths": [{"path": "/usr/bin"}]}}));
+    }
+
+    fn config() -> Config {
+        Config::parse(TOML, ConfigFormat::Toml).unwrap()
+    }
+
+    #[test]
+    fn gets_settings_by_dotted_path() {
+        let config = config();
+        assert_eq!(config.get_setting("ui.window_width").unwrap(), json!(900.0));
+        assert_eq!(config.get_setting("search.scan_paths[1].path").unwrap(), json!("/opt/apps"));
+        assert_eq!(config.get_setting("search.scan_paths[0].max_depth").unwrap(), json!(ScanRoot::new(PathBuf::new()).max_depth));
+        assert_eq!(config.get_setting("search.exclude_patterns").unwrap(), json!([FilterRule::substring("unins")]));
+        assert_eq!(config.get_setting("ui").unwrap()["window_width"], json!(900.0));
+
+        assert_eq!(config.get_setting("ui.nope").unwrap_err(), "Unknown setting: ui.nope");
+        assert_eq!(config.get_setting("search.scan_paths[5]").unwrap_err(), "Unknown setting: search.scan_paths[5]");
+        // Unset optional fields can be set, but have no value to get
+        assert!(config.get_setting("search.scan_paths[0].extensions").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_paths() {
+        for path in ["", "ui.", ".ui", "ui..window_width", "ui.window-width", "scan_paths[", "scan_paths[x]", "scan_paths[-1]", "scan_paths[0]x", "[0]"] {
+            assert_eq!(config().get_setting(path).unwrap_err(), format!("Invalid setting path: {}", path));
+        }
+    }
+
+    #[test]
+    fn sets_settings_by_dotted_path() {
+        let mut config = config();
+        config.set_setting("search.max_results", json!(25)).unwrap();
+        config.set_setting("search.scan_paths[1].follow_links", json!(true)).unwrap();
+        config.set_setting("search.scan_paths[0].extensions", json!([".sh"])).unwrap();
+        assert_eq!(config.search.max_results, 25);
+        assert!(config.search.scan_paths[1].follow_links);
+        assert_eq!(config.search.scan_paths[0].extensions, Some(vec![".sh".to_string()]));
+
+        config.set_setting("search.scan_paths[0].extensions", Value::Null).unwrap();
+        assert_eq!(config.search.scan_paths[0].extensions, None);
+
+        assert_eq!(Config::parse_setting_value("25"), json!(25));
+        assert_eq!(Config::parse_setting_value("neon"), json!("neon"));
+        assert_eq!(Config::parse_setting_value("\"neon\""), json!("neon"));
+    }
+
+    #[test]
+    fn rejected_values_leave_the_config_untouched() {
+        let mut config = config();
+        let before = config.clone();
+
+        let wrong_type = config.set_setting("search.max_results", json!("many")).unwrap_err();
+        assert!(wrong_type.starts_with("search.max_results: invalid type: string \"many\""), "{}", wrong_type);
+        assert_eq!(
+            config.set_setting("search.max_results", json!(0)).unwrap_err(),
+            "search.max_results: must be at least 1"
+        );
+        assert_eq!(
+            config.set_setting("search.scan_paths[1].max_depth", json!(0)).unwrap_err(),
+            "search.scan_paths[1].max_depth: must be at least 1"
+        );
+        assert_eq!(config.set_setting("ui.nope", json!(1)).unwrap_err(), "Unknown setting: ui.nope");
+        assert!(config.set_setting("search.exclude_patterns", json!([{"pattern": "(", "kind": "regex"}])).is_err());
+        assert_eq!(config, before);
+    }
+
+    #[test]
+    fn adds_and_removes_list_items() {
+        let mut config = config();
+        config.add_setting("search.exclude_patterns", json!("setup")).unwrap();
+        config.add_setting("search.exclude_patterns", json!({"pattern": "*.tmp", "kind": "glob"})).unwrap();
+        config.add_setting("search.scan_paths", json!("/snap/bin")).unwrap();
+        assert_eq!(config.search.exclude_patterns[1], FilterRule::substring("setup"));
+        assert_eq!(config.search.exclude_patterns[2].pattern, "*.tmp");
+        assert_eq!(config.search.scan_paths[2], ScanRoot::new(PathBuf::from("/snap/bin")));
+
+        // Plain strings match the stored rule or root
+        config.remove_setting("search.exclude_patterns", &json!("unins")).unwrap();
+        config.remove_setting("search.exclude_patterns", &json!("*.tmp")).unwrap();
+        config.remove_setting("search.scan_paths", &json!("/usr/bin")).unwrap();
+        assert_eq!(config.search.exclude_patterns, [FilterRule::substring("setup")]);
+        let paths: Vec<_> = config.search.scan_paths.iter().map(|root| root.path.clone()).collect();
+        assert_eq!(paths, [PathBuf::from("/opt/apps"), PathBuf::from("/snap/bin")]);
+
+        let before = config.clone();
+        assert_eq!(
+            config.remove_setting("search.exclude_patterns", &json!("missing")).unwrap_err(),
+            "search.exclude_patterns: \"missing\" is not in the list"
+        );
+        assert_eq!(
+            config.add_setting("search.max_results", json!(1)).unwrap_err(),
+            "search.max_results: not a list but a number"
+        );
+        assert!(config.add_setting("search.exclude_patterns", json!("")).is_err());
+        assert_eq!(config, before);
+    }
+}
@@ -0,0 +1,471 @@
+use std::{fs, path::Path};
+
+/// A parsed Windows shell link (`.lnk`) file, following the MS-SHLLINK format
+/// Only the fields the launcher needs are kept. Parsing is pure Rust and works
+/// on any platform.
+#[derive(Debug, Clone, Default)]
+pub struct ShellLink {
+    pub target_path: Option<String>,
+    pub arguments: Option<String>,
+    pub working_dir: Option<String>,
+    pub description: Option<String>,
+    pub relative_path: Option<String>,
+    pub icon_location: Option<String>,
+    pub icon_index: i32,
+    pub hotkey: Option<String>, // e.g. "Ctrl+Alt+T"
+}
+
+// ShellLinkHeader
+const HEADER_SIZE: u32 = 0x4C;
+const LINK_CLSID: [u8; 16] = [
+    0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+// LinkFlags
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x0000_0001;
+const HAS_LINK_INFO: u32 = 0x0000_0002;
+const HAS_NAME: u32 = 0x0000_0004;
+const HAS_RELATIVE_PATH: u32 = 0x0000_0008;
+const HAS_WORKING_DIR: u32 = 0x0000_0010;
+const HAS_ARGUMENTS: u32 = 0x0000_0020;
+const HAS_ICON_LOCATION: u32 = 0x0000_0040;
+const IS_UNICODE: u32 = 0x0000_0080;
+
+// LinkInfoFlags
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x1;
+const COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX: u32 = 0x2;
+
+// ExtraData block signatures
+const ENVIRONMENT_VARIABLE_DATA_BLOCK: u32 = 0xA000_0001;
+const ICON_ENVIRONMENT_DATA_BLOCK: u32 = 0xA000_0007;
+
+// HotKeyFlags modifiers (high byte)
+const HOTKEYF_SHIFT: u8 = 0x01;
+const HOTKEYF_CONTROL: u8 = 0x02;
+const HOTKEYF_ALT: u8 = 0x04;
+
+impl ShellLink {
+    /// Read and parse the shell link at `path`
+    pub fn load(path: &Path) -> Option<Self> {
+        let data = fs::read(path).ok()?;
+        let mut link = Self::parse(&data)?;
+
+        // Fall back to the relative path when no absolute target was stored
+        if link.target_path.is_none() {
+            if let (Some(relative), Some(parent)) = (&link.relative_path, path.parent()) {
+                let resolved = parent.join(relative.replace('\\', std::path::MAIN_SEPARATOR_STR));
+                link.target_path = Some(resolved.to_string_lossy().to_string());
+            }
+        }
+
+        Some(link)
+    }
+
+    /// Parse shell link bytes; returns `None` if the header is invalid or the
+    /// structure is truncated
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut reader = Reader::new(data);
+
+        if reader.u32()? != HEADER_SIZE || reader.bytes(16)? != LINK_CLSID {
+            return None;
+        }
+
+        let flags = reader.u32()?;
+        let _file_attributes = reader.u32()?;
+        reader.skip(24)?; // Creation, access and write times
+        let _file_size = reader.u32()?;
+        let icon_index = reader.u32()? as i32;
+        let _show_command = reader.u32()?;
+        let hotkey = reader.u16()?;
+        reader.skip(10)?; // Reserved
+
+        let mut link = ShellLink {
+            icon_index,
+            hotkey: Self::format_hotkey(hotkey),
+            ..Default::default()
+        };
+
+        if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+            let id_list_size = reader.u16()? as usize;
+            reader.skip(id_list_size)?;
+        }
+
+        if flags & HAS_LINK_INFO != 0 {
+            let start = reader.position;
+            let link_info_size = reader.u32()? as usize;
+            let link_info = data.get(start..start + link_info_size)?;
+            link.target_path = Self::parse_link_info(link_info);
+            reader.position = start + link_info_size;
+        }
+
+        let unicode = flags & IS_UNICODE != 0;
+        if flags & HAS_NAME != 0 {
+            link.description = Some(reader.string_data(unicode)?);
+        }
+        if flags & HAS_RELATIVE_PATH != 0 {
+            link.relative_path = Some(reader.string_data(unicode)?);
+        }
+        if flags & HAS_WORKING_DIR != 0 {
+            link.working_dir = Some(reader.string_data(unicode)?);
+        }
+        if flags & HAS_ARGUMENTS != 0 {
+            link.arguments = Some(reader.string_data(unicode)?);
+        }
+        if flags & HAS_ICON_LOCATION != 0 {
+            link.icon_location = Some(reader.string_data(unicode)?);
+        }
+
+        // Extra data is optional; a truncated block just ends the list
+        while let Some(block_size) = reader.u32() {
+            let block_size = block_size as usize;
+            if block_size < 4 {
+                break;
+            }
+
+            let start = reader.position - 4;
+            let Some(block) = data.get(start..start + block_size) else {
+                break;
+            };
+            reader.position = start + block_size;
+
+            match Reader::new(&block[4..]).u32() {
+                Some(ENVIRONMENT_VARIABLE_DATA_BLOCK) if link.target_path.is_none() => {
+                    link.target_path = Self::parse_environment_block(block).map(|t| Self::expand_env(&t));
+                }
+                Some(ICON_ENVIRONMENT_DATA_BLOCK) => {
+                    if let Some(icon) = Self::parse_environment_block(block) {
+                        link.icon_location = Some(icon);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        link.icon_location = link.icon_location.map(|icon| Self::expand_env(&icon));
+        link.working_dir = link.working_dir.map(|dir| Self::expand_env(&dir));
+
+        Some(link)
+    }
+
+    /// Icon location in the `path,index` form used by Windows resources
+    pub fn icon_resource(&self) -> Option<String> {
+        let location = self.icon_location.as_ref()?;
+        Some(if self.icon_index != 0 {
+            format!("{},{}", location, self.icon_index)
+        } else {
+            location.clone()
+        })
+    }
+
+    /// Local or network target path from a LinkInfo structure
+    fn parse_link_info(info: &[u8]) -> Option<String> {
+        let mut reader = Reader::new(info);
+        let _size = reader.u32()?;
+        let header_size = reader.u32()?;
+        let info_flags = reader.u32()?;
+        let _volume_id_offset = reader.u32()?;
+        let local_base_path_offset = reader.u32()? as usize;
+        let network_link_offset = reader.u32()? as usize;
+        let common_path_suffix_offset = reader.u32()? as usize;
+
+        // Unicode offsets are only present with the larger header
+        let (local_base_path_unicode, common_path_suffix_unicode) = if header_size >= 0x24 {
+            (Some(reader.u32()? as usize), Some(reader.u32()? as usize))
+        } else {
+            (None, None)
+        };
+
+        let suffix = match common_path_suffix_unicode {
+            Some(offset) => Reader::utf16_z(info, offset),
+            None => Reader::ansi_z(info, common_path_suffix_offset),
+        }
+        .unwrap_or_default();
+
+        if info_flags & VOLUME_ID_AND_LOCAL_BASE_PATH != 0 {
+            let base = match local_base_path_unicode {
+                Some(offset) => Reader::utf16_z(info, offset),
+                None => Reader::ansi_z(info, local_base_path_offset),
+            }?;
+            return Some(base + &suffix);
+        }
+
+        if info_flags & COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX != 0 {
+            let network = info.get(network_link_offset..)?;
+            let mut reader = Reader::new(network);
+            let _size = reader.u32()?;
+            let _flags = reader.u32()?;
+            let net_name_offset = reader.u32()? as usize;
+            let _device_name_offset = reader.u32()?;
+            let _provider_type = reader.u32()?;
+
+            let net_name = if net_name_offset > 0x14 {
+                let net_name_unicode_offset = reader.u32()? as usize;
+                Reader::utf16_z(network, net_name_unicode_offset)
+            } else {
+                Reader::ansi_z(network, net_name_offset)
+            }?;
+
+            return Some(if suffix.is_empty() {
+                net_name
+            } else {
+                format!("{}\\{}", net_name, suffix)
+            });
+        }
+
+        None
+    }
+
+    /// Target of an environment variable or icon environment block, preferring
+    /// the Unicode copy over the ANSI one
+    fn parse_environment_block(block: &[u8]) -> Option<String> {
+        // BlockSize, BlockSignature, TargetAnsi[260], TargetUnicode[520]
+        let unicode = Reader::utf16_z(block, 8 + 260).filter(|s| !s.is_empty());
+        unicode.or_else(|| Reader::ansi_z(block, 8).filter(|s| !s.is_empty()))
+    }
+
+    /// Expand `%VAR%` references from the current environment, leaving unknown
+    /// variables untouched
+    fn expand_env(value: &str) -> String {
+        let mut result = String::new();
+        let mut rest = value;
+
+        while let Some(start) = rest.find('%') {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+
+            match after.find('%') {
+                Some(end) => {
+                    let name = &after[..end];
+                    match std::env::var(name) {
+                        Ok(expanded) if !name.is_empty() => result.push_str(&expanded),
+                        _ => {
+                            result.push('%');
+                            result.push_str(name);
+                            result.push('%');
+                        }
+                    }
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    result.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+
+        result.push_str(rest);
+        result
+    }
+
+    /// Human-readable form of the HotKeyFlags structure, e.g. "Ctrl+Shift+F5"
+    fn format_hotkey(hotkey: u16) -> Option<String> {
+        let [key, modifiers] = hotkey.to_le_bytes();
+
+        let key_name = match key {
+            0x30..=0x39 | 0x41..=0x5A => (key as char).to_string(),
+            0x70..=0x87 => format!("F{}", key - 0x6F),
+            0x90 => "NumLock".to_string(),
+            0x91 => "ScrollLock".to_string(),
+            _ => return None,
+        };
+
+        let mut parts = Vec::new();
+        if modifiers & HOTKEYF_CONTROL != 0 {
+            parts.push("Ctrl".to_string());
+        }
+        if modifiers & HOTKEYF_ALT != 0 {
+            parts.push("Alt".to_string());
+        }
+        if modifiers & HOTKEYF_SHIFT != 0 {
+            parts.push("Shift".to_string());
+        }
+        parts.push(key_name);
+
+        Some(parts.join("+"))
+    }
+}
+
+/// Little-endian cursor over shell link bytes
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.position..self.position.checked_add(len)?)?;
+        self.position += len;
+        Some(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Option<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// StringData entry: a character count followed by UTF-16 or ANSI characters
+    fn string_data(&mut self, unicode: bool) -> Option<String> {
+        let count = self.u16()? as usize;
+
+        if unicode {
+            let bytes = self.bytes(count * 2)?;
+            let units: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+            Some(String::from_utf16_lossy(&units))
+        } else {
+            let bytes = self.bytes(count)?;
+            Some(bytes.iter().map(|&b| b as char).collect())
+        }
+    }
+
+    /// NUL-terminated single-byte string at `offset` (code page bytes read as Latin-1)
+    fn ansi_z(data: &[u8], offset: usize) -> Option<String> {
+        let bytes = data.get(offset..)?;
+        let end = bytes.iter().position(|&b| b == 0)?;
+        Some(bytes[..end].iter().map(|&b| b as char).collect())
+    }
+
+    /// NUL-terminated UTF-16LE string at `offset`
+    fn utf16_z(data: &[u8], offset: usize) -> Option<String> {
+        let bytes = data.get(offset..)?;
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|&unit| unit != 0)
+            .collect();
+        Some(String::from_utf16_lossy(&units))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Built to the MS-SHLLINK layout: LinkInfo with a local path, every
+    // StringData field in UTF-16 and an IDList to skip
+    const LOCAL: &[u8] = include_bytes!("../tests/fixtures/local.lnk");
+    // No LinkInfo; the target is only in an environment variable data block
+    const ENVIRONMENT: &[u8] = include_bytes!("../tests/fixtures/environment.lnk");
+    // ANSI relative path and arguments only
+    const RELATIVE: &[u8] = include_bytes!("../tests/fixtures/relative.lnk");
+
+    #[test]
+    fn parses_local_link_info_and_string_data() {
+        let link = ShellLink::parse(LOCAL).unwrap();
+
+        assert_eq!(link.target_path.as_deref(), Some(r"C:\Program Files\Fade\fade.exe"));
+        assert_eq!(link.description.as_deref(), Some("Fast app launcher"));
+        assert_eq!(link.relative_path.as_deref(), Some(r"..\Fade\fade.exe"));
+        assert_eq!(link.working_dir.as_deref(), Some(r"C:\Users\Public"));
+        assert_eq!(link.arguments.as_deref(), Some("--daemon --theme fade"));
+        assert_eq!(link.icon_location.as_deref(), Some(r"C:\Program Files\Fade\fade.ico"));
+        assert_eq!(link.icon_index, 2);
+        assert_eq!(link.icon_resource().as_deref(), Some(r"C:\Program Files\Fade\fade.ico,2"));
+        assert_eq!(link.hotkey.as_deref(), Some("Ctrl+Alt+T"));
+    }
+
+    #[test]
+    fn reads_target_and_icon_from_environment_blocks() {
+        std::env::set_var("FADE_LNK_TEST_DIR", r"C:\Tools");
+
+        let link = ShellLink::parse(ENVIRONMENT).unwrap();
+
+        assert_eq!(link.target_path.as_deref(), Some(r"C:\Tools\tool.exe"));
+        // The icon environment block replaces the IconLocation string
+        assert_eq!(link.icon_location.as_deref(), Some(r"C:\Tools\tool.ico"));
+        assert_eq!(link.hotkey, None);
+    }
+
+    #[test]
+    fn resolves_relative_path_next_to_the_link() {
+        let link = ShellLink::parse(RELATIVE).unwrap();
+        assert_eq!(link.target_path, None);
+        assert_eq!(link.relative_path.as_deref(), Some(r"..\bin\tool.bat"));
+        assert_eq!(link.arguments.as_deref(), Some("/q"));
+
+        let dir = std::env::temp_dir()
+            .join(format!("fade-launcher-shell-link-test-{}", std::process::id()))
+            .join("links");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tool.lnk");
+        fs::write(&path, RELATIVE).unwrap();
+
+        let link = ShellLink::load(&path).unwrap();
+        let expected = dir.join("..").join("bin").join("tool.bat");
+        assert_eq!(link.target_path.as_deref(), Some(expected.to_string_lossy().as_ref()));
+
+        fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn formats_hotkeys() {
+        assert_eq!(ShellLink::format_hotkey(0x0000), None);
+        assert_eq!(ShellLink::format_hotkey(0x0141), Some("Shift+A".to_string()));
+        assert_eq!(ShellLink::format_hotkey(0x0774), Some("Ctrl+Alt+Shift+F5".to_string()));
+        assert_eq!(ShellLink::format_hotkey(0x0290), Some("Ctrl+NumLock".to_string()));
+    }
+
+    #[test]
+    fn truncated_links_return_none() {
+        // Every cut before the extra data loses a required structure
+        let extra_data = LOCAL.len() - 4;
+        for len in 0..extra_data {
+            assert!(ShellLink::parse(&LOCAL[..len]).is_none(), "parsed {} bytes", len);
+        }
+
+        // Extra data is optional, so cutting into it keeps the link
+        for len in RELATIVE.len() - 4..RELATIVE.len() {
+            assert!(ShellLink::parse(&RELATIVE[..len]).is_some());
+        }
+        for len in 0..ENVIRONMENT.len() {
+            let _ = ShellLink::parse(&ENVIRONMENT[..len]);
+        }
+    }
+
+    #[test]
+    fn malformed_links_return_none() {
+        let mut wrong_size = LOCAL.to_vec();
+        wrong_size[0] = 0x4D;
+        assert!(ShellLink::parse(&wrong_size).is_none());
+
+        let mut wrong_clsid = LOCAL.to_vec();
+        wrong_clsid[4] = 0x02;
+        assert!(ShellLink::parse(&wrong_clsid).is_none());
+
+        // LinkInfo claiming more bytes than the file has
+        let link_info = HEADER_SIZE as usize + 8;
+        let mut huge_link_info = LOCAL.to_vec();
+        huge_link_info[link_info..link_info + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(ShellLink::parse(&huge_link_info).is_none());
+
+        // StringData count past the end of the file
+        let mut huge_string = RELATIVE.to_vec();
+        huge_string[HEADER_SIZE as usize..HEADER_SIZE as usize + 2].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert!(ShellLink::parse(&huge_string).is_none());
+    }
+
+    #[test]
+    fn corrupted_links_do_not_panic() {
+        // Flip bytes all over each fixture; any result is fine as long as
+        // parsing returns
+        for fixture in [LOCAL, ENVIRONMENT, RELATIVE] {
+            for position in 0..fixture.len() {
+                for value in [0x00, 0x7F, 0xFF] {
+                    let mut data = fixture.to_vec();
+                    data[position] = value;
+                    let _ = ShellLink::parse(&data);
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use eframe::egui::{Color32, Rgba};
+use eframe::egui::Color32;
 
 /// Color scheme constants for the Fade Launcher
 /// Features a beautiful gradient from pink to aqua blue
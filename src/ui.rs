@@ -1,24 +1,81 @@
-use eframe::egui::{self, *};
-use crate::{theme::FadeTheme, app::AppInfo};
+use eframe::egui::{epaint::Vertex, *};
+use crate::{theme::FadeTheme, app_info::AppInfo, config::AppearanceConfig, hotkey::Hotkey, scanner::ScanProgress};
+use std::ops::Range;
 
 pub struct LauncherUI {
     search_focused: bool,
     selected_index: usize,
+    appearance: AppearanceConfig,
+    launch_shortcut: Option<KeyboardShortcut>, // Launches the highlighted result
+    prompt: Option<String>, // Replaces the search hints when picking from a custom list
+    marked: Vec<String>, // Paths of results checked in a multi-select picker
 }
 
 impl LauncherUI {
-    pub fn new() -> Self {
+    pub fn new(appearance: &AppearanceConfig, launch_shortcut: Option<KeyboardShortcut>) -> Self {
         Self {
             search_focused: false,
             selected_index: 0,
+            appearance: appearance.clone(),
+            launch_shortcut,
+            prompt: None,
+            marked: Vec::new(),
         }
     }
     
+    /// Restyle with new appearance settings, e.g. after the config file changed
+    pub fn set_appearance(&mut self, appearance: &AppearanceConfig) {
+        self.appearance = appearance.clone();
+    }
+    
+    pub fn set_launch_shortcut(&mut self, launch_shortcut: Option<KeyboardShortcut>) {
+        self.launch_shortcut = launch_shortcut;
+    }
+    
+    /// Use the list to pick from arbitrary items, asking with `prompt`
+    pub fn set_prompt(&mut self, prompt: &str) {
+        self.prompt = Some(prompt.to_string());
+    }
+    
+    /// Results to show as checked, by path
+    pub fn set_marked(&mut self, marked: &[String]) {
+        self.marked = marked.to_vec();
+    }
+    
+    pub fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+    
+    /// Highlight the first result again, e.g. after the query changed
+    pub fn reset_selection(&mut self) {
+        self.selected_index = 0;
+    }
+    
+    /// Parse a hotkey such as "Ctrl+Shift+K" or "Enter" into an egui shortcut
+    pub fn parse_shortcut(spec: &str) -> Option<KeyboardShortcut> {
+        let hotkey = Hotkey::parse(spec)?;
+        
+        let mut modifiers = Modifiers::NONE;
+        for (held, modifier) in [
+            (hotkey.ctrl, Modifiers::CTRL),
+            (hotkey.alt, Modifiers::ALT),
+            (hotkey.shift, Modifiers::SHIFT),
+            (hotkey.command, Modifiers::COMMAND),
+        ] {
+            if held {
+                modifiers = modifiers | modifier;
+            }
+        }
+        
+        Some(KeyboardShortcut::new(modifiers, Key::from_name(&hotkey.key)?))
+    }
+    
     pub fn draw_main_window(
         &mut self,
         ui: &mut Ui,
         search_query: &mut String,
         results: &[AppInfo],
+        scan_progress: &ScanProgress,
         animation_time: f32,
         mut on_launch: impl FnMut(&AppInfo),
     ) {
@@ -38,7 +95,7 @@ impl LauncherUI {
         
         // Main content area with semi-transparent background
         let main_frame = Frame::none()
-            .fill(FadeTheme::BACKGROUND_DARK)
+            .fill(FadeTheme::BACKGROUND_DARK.gamma_multiply(self.appearance.transparency))
             .rounding(Rounding::same(20.0))
             .stroke(Stroke::new(2.0, FadeTheme::BORDER_COLOR))
             .inner_margin(Margin::same(30.0));
@@ -59,7 +116,7 @@ impl LauncherUI {
                     ui.add_space(20.0);
                     
                     // Results list
-                    self.draw_results_list(ui, results, &mut on_launch);
+                    self.draw_results_list(ui, results, scan_progress, &mut on_launch);
                     
                     // Handle keyboard navigation
                     self.handle_keyboard_navigation(ui, search_response, results, &mut on_launch);
@@ -72,11 +129,14 @@ impl LauncherUI {
         let painter = ui.painter();
         
         // Create animated gradient mesh
-        let mesh = self.create_animated_gradient_mesh(rect, animation_time);
+        let gradient_time = if self.appearance.gradient_animation { animation_time } else { 0.0 };
+        let mesh = self.create_animated_gradient_mesh(rect, gradient_time);
         painter.add(Shape::mesh(mesh));
         
         // Add some subtle animated particles/dots for extra flair
-        self.draw_animated_particles(ui, rect, animation_time);
+        if self.appearance.show_particles {
+            self.draw_animated_particles(ui, rect, animation_time);
+        }
     }
     
     fn create_animated_gradient_mesh(&self, rect: Rect, time: f32) -> Mesh {
@@ -96,7 +156,7 @@ impl LauncherUI {
                 let gradient_pos = (i as f32 / grid_size as f32) + 
                     0.3 * (time * 0.5 + j as f32 * 0.1).sin();
                 
-                let color = FadeTheme::gradient_color(gradient_pos);
+                let color = FadeTheme::gradient_color(gradient_pos).gamma_multiply(self.appearance.transparency);
                 let vertex = Vertex { pos: pos2(x, y), uv: pos2(0.0, 0.0), color };
                 mesh.vertices.push(vertex);
             }
@@ -149,7 +209,7 @@ impl LauncherUI {
                 .strong());
             
             ui.colored_label(FadeTheme::TEXT_MUTED, 
-                RichText::new("Type to search for applications...")
+                RichText::new(self.prompt.as_deref().unwrap_or("Type to search for applications..."))
                     .size(14.0));
         });
     }
@@ -165,7 +225,7 @@ impl LauncherUI {
             let search_response = ui.add_sized(
                 [ui.available_width() - 40.0, 40.0],
                 TextEdit::singleline(search_query)
-                    .hint_text("Search applications...")
+                    .hint_text(if self.prompt.is_some() { "Filter..." } else { "Search applications..." })
                     .font(TextStyle::Heading)
                     .desired_width(f32::INFINITY)
             );
@@ -180,15 +240,35 @@ impl LauncherUI {
         }).inner
     }
     
-    fn draw_results_list(&mut self, ui: &mut Ui, results: &[AppInfo], on_launch: &mut impl FnMut(&AppInfo)) {
+    fn draw_results_list(
+        &mut self,
+        ui: &mut Ui,
+        results: &[AppInfo],
+        scan_progress: &ScanProgress,
+        on_launch: &mut impl FnMut(&AppInfo),
+    ) {
         if results.is_empty() {
             ui.vertical_centered(|ui| {
                 ui.add_space(40.0);
-                ui.colored_label(FadeTheme::TEXT_MUTED, "No applications found");
+                if scan_progress.running {
+                    ui.colored_label(FadeTheme::TEXT_MUTED, scan_progress.to_string());
+                } else {
+                    let empty_text = if self.prompt.is_some() {
+                        "No matches, Enter picks what you typed"
+                    } else {
+                        "No applications found"
+                    };
+                    ui.colored_label(FadeTheme::TEXT_MUTED, empty_text);
+                }
             });
             return;
         }
         
+        // Results may still be incomplete while indexing
+        if scan_progress.running {
+            ui.colored_label(FadeTheme::TEXT_MUTED, RichText::new(scan_progress.to_string()).size(11.0));
+        }
+        
         ScrollArea::vertical()
             .max_height(250.0)
             .show(ui, |ui| {
@@ -228,17 +308,44 @@ impl LauncherUI {
                 ui.add_space(5.0);
                 
                 ui.vertical(|ui| {
-                    ui.colored_label(FadeTheme::TEXT_PRIMARY, 
-                        RichText::new(&app.name).size(16.0).strong());
+                    ui.horizontal(|ui| {
+                        if self.marked.contains(&app.path) {
+                            ui.colored_label(FadeTheme::AQUA_PRIMARY, "✔");
+                        }
+                        
+                        ui.label(Self::highlighted_text(
+                            &app.name, &app.name_matches, 16.0, FadeTheme::TEXT_PRIMARY));
+                        
+                        // Tells apart different apps with the same name
+                        if let Some(hint) = &app.path_hint {
+                            ui.colored_label(FadeTheme::TEXT_MUTED, RichText::new(hint).size(11.0));
+                        }
+                    });
                     
-                    if let Some(desc) = &app.description {
-                        ui.colored_label(FadeTheme::TEXT_SECONDARY,
-                            RichText::new(desc).size(12.0));
-                    } else {
-                        ui.colored_label(FadeTheme::TEXT_MUTED,
-                            RichText::new(&app.path).size(11.0));
+                    // Show the path instead of the description when only the path matched
+                    let path_only_match = app.name_matches.is_empty() && !app.path_matches.is_empty();
+                    
+                    match &app.description {
+                        Some(desc) if !path_only_match => {
+                            ui.colored_label(FadeTheme::TEXT_SECONDARY,
+                                RichText::new(desc).size(12.0));
+                        }
+                        // Picker items are their own path
+                        None if app.path == app.name => {}
+                        _ => {
+                            ui.label(Self::highlighted_text(
+                                &app.path, &app.path_matches, 11.0, FadeTheme::TEXT_MUTED));
+                        }
                     }
                 });
+                
+                // Shortcut key assigned to the link, if any
+                if let Some(hotkey) = &app.hotkey {
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        ui.colored_label(FadeTheme::TEXT_MUTED,
+                            RichText::new(hotkey).size(11.0).monospace());
+                    });
+                }
             });
         });
         
@@ -247,6 +354,29 @@ impl LauncherUI {
         }
     }
     
+    /// Lays out `text` with the matched character ranges painted in the theme gradient
+    fn highlighted_text(text: &str, matches: &[Range<usize>], size: f32, base_color: Color32) -> text::LayoutJob {
+        let mut job = text::LayoutJob::default();
+        let char_count = text.chars().count().max(1);
+        
+        for (index, c) in text.chars().enumerate() {
+            let mut format = TextFormat {
+                font_id: FontId::proportional(size),
+                color: base_color,
+                ..Default::default()
+            };
+            
+            if matches.iter().any(|range| range.contains(&index)) {
+                format.color = FadeTheme::gradient_color(index as f32 / char_count as f32);
+                format.underline = Stroke::new(1.0, format.color);
+            }
+            
+            job.append(c.encode_utf8(&mut [0; 4]), 0.0, format);
+        }
+        
+        job
+    }
+    
     fn handle_keyboard_navigation(
         &mut self,
         ui: &mut Ui,
@@ -260,7 +390,10 @@ impl LauncherUI {
                     self.selected_index = (self.selected_index + 1).min(results.len() - 1);
                 } else if i.key_pressed(Key::ArrowUp) {
                     self.selected_index = self.selected_index.saturating_sub(1);
-                } else if i.key_pressed(Key::Enter) {
+                } else if self
+                    .launch_shortcut
+                    .is_some_and(|shortcut| i.modifiers.matches_logically(shortcut.modifiers) && i.key_pressed(shortcut.logical_key))
+                {
                     if let Some(app) = results.get(self.selected_index) {
                         on_launch(app);
                     }
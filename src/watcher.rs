@@ -0,0 +1,562 @@
+use crate::{
+    app_info::AppInfo, config::ScanRoot, desktop_entry::DesktopEntry, filter_rules::FilterRules, scanner::ScanTracker,
+    search::AppSearcher,
+};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::HashSet,
+    mem,
+    path::{Path, PathBuf},
+    ptr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use walkdir::WalkDir;
+
+/// Keeps the application index in sync with the scan roots after the initial scan
+/// Filesystem events (inotify on Linux) are debounced and applied to the index
+/// incrementally; roots that don't exist yet are polled until they appear.
+/// Changes are held back while a full scan runs, then applied on top of its
+/// result. The watcher thread stops when this handle is dropped.
+pub struct IndexWatcher {
+    stop: Arc<AtomicBool>,
+}
+
+impl IndexWatcher {
+    pub fn start(
+        indexed_apps: Arc<Mutex<Vec<AppInfo>>>,
+        scan_roots: Vec<ScanRoot>,
+        file_extensions: Vec<String>,
+        filter_rules: Arc<FilterRules>,
+        scans: ScanTracker,
+    ) -> Option<Self> {
+        let (tx, rx) = mpsc::channel();
+
+        let watcher = match notify::recommended_watcher(tx) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                eprintln!("Failed to start filesystem watcher: {}", e);
+                return None;
+            }
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = Arc::clone(&stop);
+
+        thread::spawn(move || {
+            let watch_loop = WatchLoop {
+                watcher,
+                indexed_apps,
+                roots: scan_roots,
+                file_extensions,
+                filter_rules,
+                scans,
+                watched: HashSet::new(),
+                watched_dirs: HashSet::new(),
+            };
+            watch_loop.run(rx, &stop_flag);
+        });
+
+        Some(Self { stop })
+    }
+}
+
+impl Drop for IndexWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+struct WatchLoop {
+    watcher: RecommendedWatcher,
+    indexed_apps: Arc<Mutex<Vec<AppInfo>>>,
+    roots: Vec<ScanRoot>,
+    file_extensions: Vec<String>,
+    filter_rules: Arc<FilterRules>,
+    scans: ScanTracker,
+    watched: HashSet<PathBuf>, // Roots that exist and are watched
+    watched_dirs: HashSet<PathBuf>, // Every directory with a watch, roots included
+}
+
+/// Index updates for one batch of changed paths, gathered before the index is locked
+#[derive(Default)]
+struct Changes {
+    removed: Vec<PathBuf>,
+    upserted: Vec<AppInfo>,
+}
+
+/// Collects changed paths until events stop for [`WatchLoop::DEBOUNCE`], or for
+/// at most [`WatchLoop::MAX_DEBOUNCE_DELAY`] while they keep arriving
+#[derive(Default)]
+struct Debouncer {
+    pending: HashSet<PathBuf>,
+    first_pending: Option<Instant>,
+    last_change: Option<Instant>,
+}
+
+impl Debouncer {
+    fn add(&mut self, paths: impl IntoIterator<Item = PathBuf>, now: Instant) {
+        let mut paths = paths.into_iter().peekable();
+        if paths.peek().is_none() {
+            return;
+        }
+
+        self.pending.extend(paths);
+        self.first_pending.get_or_insert(now);
+        self.last_change = Some(now);
+    }
+
+    /// The pending paths, once they are due at `now`
+    fn take_settled(&mut self, now: Instant) -> Option<HashSet<PathBuf>> {
+        let (first, last) = (self.first_pending?, self.last_change?);
+        let settled = now.duration_since(last) >= WatchLoop::DEBOUNCE
+            || now.duration_since(first) >= WatchLoop::MAX_DEBOUNCE_DELAY;
+        if !settled {
+            return None;
+        }
+
+        self.first_pending = None;
+        self.last_change = None;
+        Some(mem::take(&mut self.pending))
+    }
+}
+
+impl WatchLoop {
+    // Quiet period before a burst of events is applied
+    const DEBOUNCE: Duration = Duration::from_millis(500);
+
+    // Apply anyway if events keep arriving for this long
+    const MAX_DEBOUNCE_DELAY: Duration = Duration::from_secs(3);
+
+    // How often missing and vanished roots are checked
+    const ROOT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+    fn run(mut self, events: Receiver<notify::Result<Event>>, stop: &AtomicBool) {
+        // The initial scan already covers roots that exist right now
+        self.sync_roots();
+
+        let mut debouncer = Debouncer::default();
+        let mut last_poll = Instant::now();
+
+        while !stop.load(Ordering::Relaxed) {
+            match events.recv_timeout(Self::DEBOUNCE) {
+                Ok(Ok(event)) => {
+                    if !matches!(event.kind, EventKind::Access(_)) {
+                        debouncer.add(event.paths, Instant::now());
+                    }
+                }
+                Ok(Err(e)) => eprintln!("Filesystem watcher error: {}", e),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            // A running scan would overwrite the changes, so they wait for its result
+            if !self.scans.is_scanning() {
+                if let Some(changed) = debouncer.take_settled(Instant::now()) {
+                    self.apply_changes(changed);
+                }
+            }
+
+            if last_poll.elapsed() >= Self::ROOT_POLL_INTERVAL {
+                debouncer.add(self.sync_roots(), Instant::now());
+                last_poll = Instant::now();
+            }
+        }
+    }
+
+    /// Starts watching roots that appeared and drops roots that disappeared,
+    /// returning both to be re-indexed
+    fn sync_roots(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+
+        for root in self.roots.clone() {
+            let exists = root.path.is_dir();
+            let is_watched = self.watched.contains(&root.path);
+
+            if exists && !is_watched {
+                self.watch_tree(&root, &root.path);
+                self.watched.insert(root.path.clone());
+                changed.push(root.path);
+            } else if !exists && is_watched {
+                eprintln!("Scan root disappeared: {:?}", root.path);
+                self.unwatch_tree(&root.path);
+                self.watched.remove(&root.path);
+                changed.push(root.path);
+            }
+        }
+
+        changed
+    }
+
+    /// Watches `dir` and the directories below it whose files the root indexes.
+    /// Each directory gets its own non-recursive watch, so the root's depth limit
+    /// also limits the watches: a recursive inotify watch would cover the whole
+    /// subtree and could run into `max_user_watches`.
+    fn watch_tree(&mut self, root: &ScanRoot, dir: &Path) {
+        let walker = WalkDir::new(dir)
+            .follow_links(root.follow_links)
+            .into_iter()
+            .filter_entry(|e| {
+                e.file_type().is_dir()
+                    && root.covers(e.path())
+                    && root.depth_of(e.path()).is_some_and(|depth| depth < root.max_depth)
+            })
+            .filter_map(|e| e.ok());
+
+        for entry in walker {
+            let path = entry.path();
+            if self.watched_dirs.contains(path) {
+                continue;
+            }
+
+            match self.watcher.watch(path, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    self.watched_dirs.insert(path.to_path_buf());
+                }
+                Err(e) => eprintln!("Failed to watch {:?}: {}", path, e),
+            }
+        }
+    }
+
+    /// Drops the watches on `path` and below it
+    fn unwatch_tree(&mut self, path: &Path) {
+        let below: Vec<PathBuf> = self.watched_dirs.iter().filter(|dir| dir.starts_with(path)).cloned().collect();
+        for dir in below {
+            // Watches on deleted directories are already gone
+            let _ = self.watcher.unwatch(&dir);
+            self.watched_dirs.remove(&dir);
+        }
+    }
+
+    /// Re-indexes every changed path: new or modified files are added or updated,
+    /// new directories are walked, and missing paths are removed with their contents.
+    /// Files are read before the index is locked, so searches don't wait on the disk.
+    fn apply_changes(&mut self, changed: HashSet<PathBuf>) {
+        let mut changes = Changes::default();
+
+        for path in changed {
+            let Some(root) = ScanRoot::closest(&self.roots, &path).cloned() else {
+                continue;
+            };
+            if !root.covers(&path) {
+                continue;
+            }
+
+            if path.is_dir() {
+                self.watch_tree(&root, &path);
+
+                let walker = WalkDir::new(&path)
+                    .follow_links(root.follow_links)
+                    .into_iter()
+                    .filter_entry(|e| root.covers(e.path()))
+                    .filter_map(|e| e.ok());
+
+                for entry in walker {
+                    self.collect_file(entry.path(), false, &mut changes);
+                }
+            } else if path.exists() {
+                self.collect_file(&path, true, &mut changes);
+            } else {
+                self.unwatch_tree(&path);
+                self.collect_removed(&path, &mut changes);
+            }
+        }
+
+        // Entries that lose their primary file are rebuilt from their remaining
+        // sources, which are read with the index unlocked again
+        let mut done: HashSet<PathBuf> = changes.upserted.iter().map(|a| PathBuf::from(&a.path)).collect();
+        loop {
+            let Ok(mut indexed) = self.indexed_apps.lock() else {
+                return;
+            };
+
+            let mut orphaned = Vec::new();
+            for path in &changes.removed {
+                orphaned.extend(Self::remove_under(&mut indexed, path));
+            }
+            for app_info in changes.upserted {
+                orphaned.extend(Self::upsert(&mut indexed, app_info));
+            }
+
+            AppSearcher::assign_path_hints(&mut indexed);
+            indexed.sort_by_key(|a| a.name.to_lowercase());
+            drop(indexed);
+
+            orphaned.retain(|source| done.insert(source.clone()));
+            if orphaned.is_empty() {
+                break;
+            }
+            changes = Changes {
+                removed: Vec::new(),
+                upserted: orphaned.iter().filter_map(|source| self.index_file(source)).collect(),
+            };
+        }
+    }
+
+    /// Queues the index update for an existing file. With `remove_unindexed`, a
+    /// file that isn't an app (any more) is dropped from the index.
+    fn collect_file(&self, path: &Path, remove_unindexed: bool, changes: &mut Changes) {
+        match self.index_file(path) {
+            Some(app_info) => changes.upserted.push(app_info),
+            None if remove_unindexed => changes.removed.push(path.to_path_buf()),
+            None => {}
+        }
+
+        // Overrides the same desktop file ID in later roots, even if hidden itself
+        changes.removed.extend(self.same_desktop_id(path, false));
+    }
+
+    /// Queues the removal of a missing path, bringing back what it overrode: the
+    /// same desktop file ID in a later root, or for a vanished root, every desktop
+    /// file in the roots after it
+    fn collect_removed(&self, path: &Path, changes: &mut Changes) {
+        changes.removed.push(path.to_path_buf());
+
+        if let Some(next) = self.same_desktop_id(path, false).first() {
+            changes.upserted.extend(self.index_file(next));
+        }
+
+        let Some(position) = self.roots.iter().position(|root| root.path == path) else {
+            return;
+        };
+        for root in &self.roots[position + 1..] {
+            let walker = WalkDir::new(&root.path)
+                .max_depth(root.max_depth)
+                .follow_links(root.follow_links)
+                .into_iter()
+                .filter_entry(|e| root.covers(e.path()))
+                .filter_map(|e| e.ok());
+
+            for entry in walker {
+                if DesktopEntry::desktop_file_id(&root.path, entry.path()).is_some() {
+                    changes.upserted.extend(self.index_file(entry.path()));
+                }
+            }
+        }
+    }
+
+    /// Index entry for a file, using the settings of the root it was found in.
+    /// Like in the full scan, a desktop file ID seen in an earlier root overrides
+    /// this one.
+    fn index_file(&self, path: &Path) -> Option<AppInfo> {
+        if !self.same_desktop_id(path, true).is_empty() {
+            return None;
+        }
+
+        let root = ScanRoot::closest(&self.roots, path)?;
+        AppSearcher::index_entry(path, root.extensions(&self.file_extensions), &self.filter_rules)
+    }
+
+    /// Files with the same desktop file ID as `path` in the roots before the one
+    /// it's in (`earlier`) or after it, in root order
+    fn same_desktop_id(&self, path: &Path, earlier: bool) -> Vec<PathBuf> {
+        let Some(root) = ScanRoot::closest(&self.roots, path) else {
+            return Vec::new();
+        };
+        let Some(id) = DesktopEntry::desktop_file_id(&root.path, path) else {
+            return Vec::new();
+        };
+        let Some(position) = self.roots.iter().position(|r| ptr::eq(r, root)) else {
+            return Vec::new();
+        };
+
+        let roots = if earlier {
+            &self.roots[..position]
+        } else {
+            &self.roots[position + 1..]
+        };
+        roots
+            .iter()
+            .flat_map(|root| {
+                DesktopEntry::desktop_file_paths(&root.path, &id)
+                    .into_iter()
+                    .filter(|other| root.covers(other))
+            })
+            .filter(|other| other != path)
+            .collect()
+    }
+
+    /// Replaces the index data of one file, merging it with any entry for the same
+    /// target like the full scan does. Returns the sources left without an entry.
+    fn upsert(apps: &mut Vec<AppInfo>, app_info: AppInfo) -> Vec<PathBuf> {
+        let orphaned = Self::remove_under(apps, Path::new(&app_info.path));
+
+        let key = AppSearcher::dedupe_key(&app_info);
+        match apps.iter_mut().find(|a| AppSearcher::dedupe_key(a) == key) {
+            Some(existing) => AppSearcher::merge_into(existing, app_info),
+            None => apps.push(app_info),
+        }
+
+        orphaned
+    }
+
+    /// Removes `path` and everything below it from the index. Returns the other
+    /// sources of merged entries that lost their primary file, to be re-indexed.
+    fn remove_under(apps: &mut Vec<AppInfo>, path: &Path) -> Vec<PathBuf> {
+        let mut orphaned = Vec::new();
+
+        apps.retain_mut(|app| {
+            app.sources.retain(|source| !Path::new(source).starts_with(path));
+
+            if Path::new(&app.path).starts_with(path) {
+                orphaned.extend(app.sources.drain(..).map(PathBuf::from));
+                false
+            } else {
+                true
+            }
+        });
+
+        orphaned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Fresh, empty directory for one test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fade-launcher-watch-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Watch loop over `roots` indexing `.app` files into `indexed`
+    fn watch_loop(roots: Vec<ScanRoot>, indexed: Vec<AppInfo>) -> WatchLoop {
+        WatchLoop {
+            watcher: notify::recommended_watcher(|_| {}).unwrap(),
+            indexed_apps: Arc::new(Mutex::new(indexed)),
+            roots,
+            file_extensions: vec![".app".to_string()],
+            filter_rules: Arc::new(FilterRules::new(&[], &[])),
+            scans: ScanTracker::default(),
+            watched: HashSet::new(),
+            watched_dirs: HashSet::new(),
+        }
+    }
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn changes_wait_for_a_quiet_period() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::default();
+
+        debouncer.add(paths(&["/a"]), start);
+        assert_eq!(debouncer.take_settled(start + Duration::from_millis(100)), None);
+
+        // Another event restarts the quiet period
+        debouncer.add(paths(&["/b", "/a"]), start + Duration::from_millis(400));
+        assert_eq!(debouncer.take_settled(start + Duration::from_millis(800)), None);
+
+        let settled = debouncer.take_settled(start + Duration::from_millis(900));
+        assert_eq!(settled, Some(paths(&["/a", "/b"]).into_iter().collect()));
+        assert_eq!(debouncer.take_settled(start + Duration::from_secs(10)), None);
+    }
+
+    #[test]
+    fn a_steady_stream_of_changes_is_applied_after_the_max_delay() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::default();
+
+        for step in 0..8 {
+            let now = start + Duration::from_millis(400) * step;
+            debouncer.add(paths(&["/busy"]), now);
+            assert_eq!(debouncer.take_settled(now), None);
+        }
+
+        let now = start + WatchLoop::MAX_DEBOUNCE_DELAY;
+        assert_eq!(debouncer.take_settled(now).map(|p| p.len()), Some(1));
+    }
+
+    #[test]
+    fn no_changes_start_no_timer() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::default();
+
+        debouncer.add(Vec::new(), start);
+        assert_eq!(debouncer.take_settled(start + Duration::from_secs(10)), None);
+    }
+
+    #[test]
+    fn watches_directories_only_down_to_the_depth_limit() {
+        let dir = temp_dir("depth");
+        fs::create_dir_all(dir.join("a/b/c")).unwrap();
+        fs::create_dir_all(dir.join(".hidden")).unwrap();
+        let root = ScanRoot {
+            max_depth: 2,
+            ..ScanRoot::new(dir.clone())
+        };
+
+        let mut watch_loop = watch_loop(vec![root], Vec::new());
+        assert_eq!(watch_loop.sync_roots(), vec![dir.clone()]);
+        assert_eq!(watch_loop.watched_dirs, HashSet::from([dir.clone(), dir.join("a")]));
+
+        // New directories are watched, deleted ones forgotten
+        fs::create_dir(dir.join("new")).unwrap();
+        watch_loop.apply_changes(HashSet::from([dir.join("new")]));
+        assert!(watch_loop.watched_dirs.contains(&dir.join("new")));
+
+        fs::remove_dir_all(dir.join("a")).unwrap();
+        watch_loop.apply_changes(HashSet::from([dir.join("a")]));
+        assert_eq!(watch_loop.watched_dirs, HashSet::from([dir.clone(), dir.join("new")]));
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(watch_loop.sync_roots(), [dir]);
+        assert!(watch_loop.watched_dirs.is_empty());
+    }
+
+    #[test]
+    fn applies_new_and_removed_files() {
+        let dir = temp_dir("files");
+        let mut watch_loop = watch_loop(vec![ScanRoot::new(dir.clone())], Vec::new());
+
+        fs::write(dir.join("tool.app"), "").unwrap();
+        watch_loop.apply_changes(HashSet::from([dir.join("tool.app")]));
+        assert_eq!(watch_loop.indexed_apps.lock().unwrap()[0].name, "Tool");
+
+        fs::remove_file(dir.join("tool.app")).unwrap();
+        watch_loop.apply_changes(HashSet::from([dir.join("tool.app")]));
+        assert!(watch_loop.indexed_apps.lock().unwrap().is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn other_sources_are_reindexed_when_the_primary_file_goes() {
+        let dir = temp_dir("orphan");
+        fs::write(dir.join("gawk.app"), "").unwrap();
+        std::os::unix::fs::symlink(dir.join("gawk.app"), dir.join("awk.app")).unwrap();
+
+        // The symlink was found first, so it is the merged entry's primary file
+        let extensions = [".app".to_string()];
+        let rules = FilterRules::new(&[], &[]);
+        let apps = ["awk.app", "gawk.app"]
+            .iter()
+            .filter_map(|name| AppSearcher::index_entry(&dir.join(name), &extensions, &rules))
+            .collect();
+        let merged = AppSearcher::merge_duplicates(apps);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].sources.len(), 2);
+
+        let mut watch_loop = watch_loop(vec![ScanRoot::new(dir.clone())], merged);
+        fs::remove_file(dir.join("awk.app")).unwrap();
+        watch_loop.apply_changes(HashSet::from([dir.join("awk.app")]));
+
+        let indexed = watch_loop.indexed_apps.lock().unwrap();
+        assert_eq!(indexed.len(), 1);
+        assert_eq!(indexed[0].path, dir.join("gawk.app").to_string_lossy());
+        assert_eq!(indexed[0].sources, [dir.join("gawk.app").to_string_lossy()]);
+        drop(indexed);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::config::UiConfig;
+use eframe::egui;
+
+/// Window settings shared by the launcher and the dmenu picker
+pub fn native_options(ui: &UiConfig) -> eframe::NativeOptions {
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size([ui.window_width, ui.window_height])
+        .with_min_inner_size([600.0, 400.0])
+        .with_decorations(false) // Remove window decorations for sleek look
+        .with_transparent(true)
+        .with_resizable(true);
+    if ui.always_on_top {
+        viewport = viewport.with_always_on_top();
+    }
+
+    eframe::NativeOptions {
+        viewport,
+        ..Default::default()
+    }
+}
+
+/// Whether the window can be hidden and shown again. Winit ignores hiding on
+/// Wayland, which it picks over X11 whenever a Wayland display is set.
+pub fn can_hide_window() -> bool {
+    if cfg!(any(target_os = "windows", target_os = "macos")) {
+        return true;
+    }
+    let wayland = ["WAYLAND_DISPLAY", "WAYLAND_SOCKET"]
+        .iter()
+        .any(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()));
+    !wayland
+}
+
+pub fn setup_custom_fonts(ctx: &egui::Context) {
+    let fonts = egui::FontDefinitions::default();
+    
+    // You can add custom fonts here later
+    // fonts.font_data.insert(
+    //     "custom_font".to_owned(),
+    //     egui::FontData::from_static(include_bytes!("../assets/font.ttf")),
+    // );
+    
+    ctx.set_fonts(fonts);
+}
+
+pub fn setup_style(ctx: &egui::Context) {
+    let mut style = (*ctx.style()).clone();
+    
+    // Set the overall theme to dark to complement our gradient
+    ctx.set_visuals(egui::Visuals::dark());
+    
+    // Customize spacing and rounding
+    style.spacing.item_spacing = egui::vec2(12.0, 8.0);
+    style.spacing.button_padding = egui::vec2(16.0, 8.0);
+    style.visuals.window_rounding = egui::Rounding::same(15.0);
+    style.visuals.menu_rounding = egui::Rounding::same(10.0);
+    
+    ctx.set_style(style);
+}
\ No newline at end of file